@@ -1,9 +1,14 @@
-use bevy::{audio::Volume, prelude::*};
+use bevy::{
+    audio::{AudioPlaySet, Volume},
+    prelude::*,
+    transform::TransformSystem,
+};
 
 use crate::{
     gameplay::{
-        health::{Health, OnDamage, OnDeath},
+        health::{Health, OnDamage},
         player::Player,
+        respawn::GameOver,
         waves::{GameWon, WaveFinishedPreparing, WaveStartedPreparing},
     },
     menus::{Menu, game_won::GameWonMusic},
@@ -12,18 +17,13 @@ use crate::{
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Music>();
     app.register_type::<SoundEffect>();
-    app.register_type::<SpedUpSoundEffect>();
+    app.register_type::<(SpatialVoice, VoicePriority)>();
     app.add_systems(OnExit(Menu::None), suppress_soundtrack);
     app.add_systems(OnEnter(Menu::None), normalize_soundtrack);
 
-    app.add_systems(
-        Update,
-        sped_up_sound_effect_setup.run_if(run_once.or(resource_changed::<GlobalVolume>)),
-    );
-
     app.add_observer(adjust_music_to_health);
     app.add_observer(on_game_won);
-    app.add_observer(on_death);
+    app.add_observer(on_game_over);
 
     app.add_observer(on_wave_started);
     app.add_observer(on_wave_ended);
@@ -32,6 +32,12 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         apply_global_volume.run_if(resource_changed::<GlobalVolume>),
     );
+    app.add_systems(
+        PostUpdate,
+        enforce_voice_budget
+            .after(TransformSystem::TransformPropagate)
+            .before(AudioPlaySet),
+    );
 }
 
 /// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
@@ -60,25 +66,58 @@ pub(crate) fn sound_effect(handle: Handle<AudioSource>) -> impl Bundle {
     (AudioPlayer(handle), PlaybackSettings::DESPAWN, SoundEffect)
 }
 
-#[derive(Component, Reflect, Default)]
+/// The maximum number of [`SpatialVoice`]s that may play at the same time.
+const MAX_SPATIAL_VOICES: usize = 24;
+/// Voices that are already playing count as this much more important, so that they don't get cut off
+/// by a slightly more important sound and then come back a frame later.
+const PLAYING_VOICE_BONUS: f32 = 1.5;
+
+/// A spatial sound effect that counts towards the voice budget.
+/// When there are more than [`MAX_SPATIAL_VOICES`], the least important ones are despawned,
+/// judged by their priority and their distance to the listener.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
 #[reflect(Component)]
-pub(crate) struct SpedUpSoundEffect;
-
-pub(crate) fn sped_up_sound_effect(handle: Handle<AudioSource>) -> impl Bundle {
-    (
-        AudioPlayer(handle),
-        PlaybackSettings::DESPAWN,
-        SpedUpSoundEffect,
-    )
+pub(crate) struct SpatialVoice(pub(crate) VoicePriority);
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum VoicePriority {
+    /// Sounds that are only there for ambience, like footsteps and grunts.
+    Low,
+    /// Sounds that tell the player what an enemy is doing.
+    Normal,
+    /// Sounds the player must not miss, like explosions.
+    High,
 }
 
-fn sped_up_sound_effect_setup(
-    global_volume: Res<GlobalVolume>,
-    mut audio_query: Query<(&PlaybackSettings, &mut AudioSink), With<SpedUpSoundEffect>>,
+impl VoicePriority {
+    fn weight(self) -> f32 {
+        match self {
+            VoicePriority::Low => 1.0,
+            VoicePriority::Normal => 3.0,
+            VoicePriority::High => 10.0,
+        }
+    }
+}
+
+fn enforce_voice_budget(
+    voices: Query<(Entity, &SpatialVoice, &GlobalTransform, Has<AudioSink>)>,
+    listener: Single<&GlobalTransform, With<SpatialListener>>,
+    mut commands: Commands,
 ) {
-    for (playback, mut sink) in &mut audio_query {
-        sink.set_speed(2.0);
-        sink.set_volume(playback.volume * global_volume.volume * Volume::Linear(2.0));
+    if voices.iter().count() <= MAX_SPATIAL_VOICES {
+        return;
+    }
+    let mut voices = voices
+        .iter()
+        .map(|(entity, voice, transform, playing)| {
+            let distance = transform.translation().distance(listener.translation());
+            let bonus = if playing { PLAYING_VOICE_BONUS } else { 1.0 };
+            (entity, voice.0.weight() * bonus / (1.0 + distance))
+        })
+        .collect::<Vec<_>>();
+    voices.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    for (entity, _) in voices.into_iter().skip(MAX_SPATIAL_VOICES) {
+        commands.entity(entity).despawn();
     }
 }
 
@@ -143,15 +182,10 @@ fn on_game_won(
     }
 }
 
-fn on_death(
-    trigger: Trigger<OnDeath>,
-    player: Query<(), With<Player>>,
+fn on_game_over(
+    _trigger: Trigger<GameOver>,
     mut audio_query: Query<(&PlaybackSettings, &mut AudioSink), With<Music>>,
 ) {
-    if !player.contains(trigger.target()) {
-        return;
-    }
-
     for (_, sink) in &mut audio_query {
         let speed_variation = 0.5;
         sink.set_speed(speed_variation);
@@ -0,0 +1,182 @@
+//! Visual feedback for damage taken by the player:
+//! HUD arcs pointing towards the source of the damage, and a red, desaturated screen at low health.
+
+use bevy::{color::palettes::tailwind, prelude::*, render::view::ColorGrading, ui::Val::*};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    gameplay::{
+        health::{Health, OnDamage},
+        player::{
+            Player,
+            camera::{PlayerCamera, WorldModelCamera},
+        },
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(DamageFeedbackSettings, DamageIndicator, LowHealthOverlay)>();
+    app.init_resource::<DamageFeedbackSettings>();
+    app.add_observer(spawn_damage_indicator);
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_low_health_overlay);
+    app.add_systems(
+        Update,
+        (update_damage_indicators, update_low_health_effect).run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Below this health fraction, the low health effect starts fading in.
+const LOW_HEALTH_THRESHOLD: f32 = 0.5;
+/// How far from the center of the screen the damage indicators are placed.
+const INDICATOR_DISTANCE: f32 = 160.0;
+
+/// Accessibility settings for the damage feedback.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub(crate) struct DamageFeedbackSettings {
+    /// How strong the damage indicators and the low health effect are, between 0 and 1.
+    pub(crate) strength: f32,
+}
+
+impl Default for DamageFeedbackSettings {
+    fn default() -> Self {
+        Self { strength: 1.0 }
+    }
+}
+
+/// An arc on the HUD that points towards where damage came from.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct DamageIndicator {
+    /// The world position the damage came from.
+    source: Vec3,
+    timer: Timer,
+}
+
+/// A full screen overlay that tints the screen red at low health.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct LowHealthOverlay;
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn spawn_damage_indicator(
+    trigger: Trigger<OnDamage>,
+    player: Query<&Transform, With<Player>>,
+    settings: Res<DamageFeedbackSettings>,
+    state: Res<State<Screen>>,
+    mut commands: Commands,
+) {
+    if *state != Screen::Gameplay || settings.strength <= 0.0 {
+        return;
+    }
+    let Ok(player_transform) = player.get(trigger.target()) else {
+        return;
+    };
+    let damage = trigger.event();
+    // Prefer the exact point of the damage, but fall back to the direction it came from.
+    let source = match (damage.point, damage.direction) {
+        (Some(point), _) => point,
+        (None, Some(direction)) => player_transform.translation - direction * 10.0,
+        (None, None) => return,
+    };
+
+    commands
+        .spawn((
+            Name::new("Damage Indicator"),
+            DamageIndicator {
+                source,
+                timer: Timer::from_seconds(1.0, TimerMode::Once),
+            },
+            StateScoped(Screen::Gameplay),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Percent(50.0),
+                top: Percent(50.0),
+                width: Px(0.0),
+                height: Px(0.0),
+                ..default()
+            },
+            Pickable::IGNORE,
+        ))
+        .with_child((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Px(-60.0),
+                top: Px(-INDICATOR_DISTANCE),
+                width: Px(120.0),
+                height: Px(10.0),
+                ..default()
+            },
+            BorderRadius::MAX,
+            BackgroundColor(Color::from(tailwind::RED_600.with_alpha(0.0))),
+            Pickable::IGNORE,
+        ));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_damage_indicators(
+    mut indicators: Query<(Entity, &mut DamageIndicator, &mut Transform, &Children)>,
+    mut backgrounds: Query<&mut BackgroundColor>,
+    player: Single<&Transform, (With<Player>, Without<DamageIndicator>)>,
+    camera: Single<&Transform, (With<PlayerCamera>, Without<DamageIndicator>)>,
+    settings: Res<DamageFeedbackSettings>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut indicator, mut transform, children) in &mut indicators {
+        indicator.timer.tick(time.delta());
+        if indicator.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Rotate the indicator so that it points towards the source relative to where we are looking.
+        // An angle of 0 means the source is straight ahead, i.e. at the top of the screen.
+        let local = camera.rotation.inverse() * (indicator.source - player.translation);
+        let angle = local.x.atan2(-local.z);
+        transform.rotation = Quat::from_rotation_z(angle);
+
+        let alpha = indicator.timer.fraction_remaining() * 0.8 * settings.strength;
+        for child in children.iter() {
+            if let Ok(mut background) = backgrounds.get_mut(child) {
+                background.0.set_alpha(alpha);
+            }
+        }
+    }
+}
+
+fn spawn_low_health_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Low Health Overlay"),
+        LowHealthOverlay,
+        StateScoped(Screen::Gameplay),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Percent(100.0),
+            height: Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::from(tailwind::RED_900.with_alpha(0.0))),
+        Pickable::IGNORE,
+    ));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_low_health_effect(
+    health: Single<Option<&Health>, With<Player>>,
+    mut overlay: Single<&mut BackgroundColor, With<LowHealthOverlay>>,
+    mut color_grading: Query<&mut ColorGrading, With<WorldModelCamera>>,
+    settings: Res<DamageFeedbackSettings>,
+) {
+    let fraction = health.map_or(0.0, Health::fraction);
+    // 0 at the threshold, 1 at zero health.
+    let intensity = ((LOW_HEALTH_THRESHOLD - fraction) / LOW_HEALTH_THRESHOLD).clamp(0.0, 1.0)
+        * settings.strength;
+
+    overlay.0.set_alpha(intensity * 0.35);
+    for mut color_grading in &mut color_grading {
+        color_grading.global.post_saturation = 1.0 - intensity * 0.7;
+    }
+}
@@ -18,7 +18,7 @@ use rand::Rng as _;
 use super::{OnExplode, assets::ExplosionAssets};
 use crate::{
     RenderLayer,
-    audio::SoundEffect,
+    audio::{SoundEffect, SpatialVoice, VoicePriority},
     despawn_after::DespawnAfter,
     gameplay::{
         explosion::ExplodeOnDeath,
@@ -69,6 +69,7 @@ fn on_explode_prop(
                 .with_volume(Volume::Linear(3.5))
                 .with_spatial_scale(SpatialScale::new(1.0 / 10.0)),
             SoundEffect,
+            SpatialVoice(VoicePriority::High),
         ));
     }
 
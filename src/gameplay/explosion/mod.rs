@@ -2,16 +2,18 @@ pub(crate) mod assets;
 pub(crate) mod effects;
 
 use avian3d::prelude::*;
-use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy::{ecs::system::SystemParam, platform::collections::HashMap, prelude::*};
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
 
 use crate::{
+    PostPhysicsAppSystems,
     auto_timer::{AutoTimer, OnAutoTimerFinish},
     despawn_after::Despawn,
     gameplay::{
-        health::{Health, OnDamage, OnDeath},
-        player::{Player, gunplay::WeaponStats},
+        health::{DamageKind, DamageSource, Health, OnDamage, OnDeath},
+        player::{Player, gunplay::WeaponStats, movement::OnKnockback},
+        status_effects::{OnStatusEffect, StatusEffectSpec},
     },
     third_party::avian3d::CollisionLayer,
 };
@@ -22,11 +24,20 @@ pub(super) fn plugin(app: &mut App) {
     app.add_plugins((assets::plugin, effects::plugin));
 
     app.register_type::<(Explosive, ExplodeOnShoot, ExplodeOnContact)>();
+    app.register_type::<PlayerExplosionRules>();
+    app.init_resource::<PendingExplosions>();
+    app.init_resource::<PlayerExplosionRules>();
 
     app.add_observer(on_shoot_explosive);
     app.add_observer(on_touch_explosive);
     app.add_observer(on_enemy_death);
     app.add_observer(on_explode);
+    app.add_systems(
+        Update,
+        resolve_explosions
+            .run_if(|pending: Res<PendingExplosions>| !pending.is_empty())
+            .in_set(PostPhysicsAppSystems::Update),
+    );
 
     // Insert `CollisionEventsEnabled` for all entities that can explode on contact,
     // and their child colliders.
@@ -62,6 +73,10 @@ pub(crate) struct Explosive {
     pub(crate) damage: f32,
     /// Whether the explosion damages the player.
     pub(crate) damages_player: bool,
+    /// A status effect applied to everything damaged by the explosion,
+    /// e.g. [`StatusEffectKind::Burning`](crate::gameplay::status_effects::StatusEffectKind::Burning)
+    /// for incendiary explosives.
+    pub(crate) status_effect: Option<StatusEffectSpec>,
 }
 
 impl Default for Explosive {
@@ -71,13 +86,64 @@ impl Default for Explosive {
             impulse_strength: 25.0,
             damage: 100.0,
             damages_player: true,
+            status_effect: None,
+        }
+    }
+}
+
+/// How explosions treat the player, on top of the player's [`DamageMultipliers`](crate::gameplay::health::DamageMultipliers).
+///
+/// Explosions the player set off themselves, e.g. by shooting a barrel, count as self-inflicted.
+/// Along with [`WeaponStats::blast_jump`] and [`WeaponStats::self_blast_damage`], these rules decide
+/// whether blasting yourself is a way to get around or just a way to get hurt.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct PlayerExplosionRules {
+    /// Scales the knockback of every explosion on the player.
+    pub(crate) knockback_scale: f32,
+    /// The largest shove a single batch of explosions can give the player,
+    /// so that chain reactions don't fling the player out of the level.
+    pub(crate) max_knockback: f32,
+    /// Scales the damage of self-inflicted explosions.
+    pub(crate) self_damage_scale: f32,
+    /// Whether self-inflicted explosions can kill the player.
+    pub(crate) lethal_self_damage: bool,
+}
+
+impl Default for PlayerExplosionRules {
+    fn default() -> Self {
+        Self {
+            knockback_scale: 0.8,
+            max_knockback: 30.0,
+            self_damage_scale: 0.5,
+            lethal_self_damage: false,
         }
     }
 }
 
 /// An event that is triggered when an explosive should explode.
-#[derive(Event, Clone, Copy, Debug, PartialEq)]
-pub(crate) struct OnExplode;
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct OnExplode {
+    /// The entity responsible for the explosion, e.g. the player that shot a barrel.
+    /// Blasts count as self-inflicted when this is the entity they hit.
+    pub(crate) instigator: Option<Entity>,
+    /// The explosive that started the chain reaction this explosion is part of, if any.
+    pub(crate) chain: Option<Entity>,
+}
+
+impl OnExplode {
+    /// Creates an explosion caused by the death of the explosive,
+    /// continuing the chain reaction of the damage that killed it.
+    pub(crate) fn caused_by(death: &OnDeath) -> Self {
+        Self {
+            instigator: death.killer,
+            chain: match death.source {
+                Some(DamageSource::Chain(root)) => Some(root),
+                _ => None,
+            },
+        }
+    }
+}
 
 /// A marker component for entities that have exploded or are in the process of exploding.
 #[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
@@ -132,7 +198,9 @@ fn on_shoot_explosive(
     let entity = trigger.target();
     if let Ok(_explosive) = explosive_query.get(entity) {
         // Trigger the explosion.
-        commands.entity(entity).trigger(OnExplode);
+        commands
+            .entity(entity)
+            .trigger(OnExplode::caused_by(trigger.event()));
     }
 }
 
@@ -162,8 +230,12 @@ fn on_touch_explosive(
         return;
     }
 
-    // Trigger the explosion.
-    commands.entity(body).trigger(OnExplode);
+    // Whoever touched the explosive didn't set it off on purpose, so the explosive is responsible for its own blast.
+    // Otherwise a kamikaze running into the player would count as a self-inflicted blast.
+    commands.entity(body).trigger(OnExplode {
+        instigator: Some(body),
+        chain: None,
+    });
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -183,6 +255,7 @@ fn on_enemy_death(
         // Hacky, but this is a game jam :D could be cleaned up though
         let mut explosive = *explosive;
         explosive.radius += weapon_stats.extra_enemy_explosion_radius;
+        let cause = OnExplode::caused_by(trigger.event());
         commands
             .spawn((
                 RigidBody::Static,
@@ -192,11 +265,8 @@ fn on_enemy_death(
                 explosive,
             ))
             .observe(
-                |trigger: Trigger<OnAutoTimerFinish>, mut commands: Commands| {
-                    commands
-                        .entity(trigger.target())
-                        .trigger(OnExplode)
-                        .despawn();
+                move |trigger: Trigger<OnAutoTimerFinish>, mut commands: Commands| {
+                    commands.entity(trigger.target()).trigger(cause).despawn();
                 },
             );
     }
@@ -206,7 +276,8 @@ fn on_enemy_death(
 fn on_explode(
     trigger: Trigger<OnExplode>,
     query: Query<(&Explosive, &GlobalTransform, &ComputedCenterOfMass), Without<Exploded>>,
-    mut explosion_helper: ExplosionHelper,
+    mut pending_explosions: ResMut<PendingExplosions>,
+    mut commands: Commands,
 ) {
     let entity = trigger.target();
 
@@ -222,28 +293,63 @@ fn on_explode(
     //
     // If we do want to support re-exploding, we could remove this component
     // at the end of the frame.
-    //
-    // NOTE: We must use the command queue from the `ExplosionHelper` here
-    //       to get the correct ordering and avoid infinite recursion.
-    //       Using `Commands` that are local to this system breaks things :D
-    //       (It took me an hour to figure this out...)
-    explosion_helper
-        .commands
-        .entity(entity)
-        .try_insert(Exploded);
-
-    // Apply the explosion at the center of mass of the explosive.
-    explosion_helper.apply_explosion(explosive, explosive_global_com);
+    commands.entity(entity).try_insert(Exploded);
+
+    // Queue the explosion at the center of mass of the explosive.
+    // All explosions of a tick are resolved together in `resolve_explosions`.
+    let cause = trigger.event();
+    pending_explosions.push(PendingExplosion {
+        explosive: *explosive,
+        point: explosive_global_com,
+        instigator: cause.instigator,
+        chain: cause.chain.unwrap_or(entity),
+    });
 
     // Despawn the explosive entity after the explosion.
-    explosion_helper.commands.entity(entity).insert(Despawn);
+    commands.entity(entity).insert(Despawn);
+}
+
+/// Explosions that have been triggered, but not yet applied to the world.
+///
+/// Large chain reactions can set off dozens of explosives in the same tick,
+/// so we collect them here and resolve them in a single batch.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub(crate) struct PendingExplosions(Vec<PendingExplosion>);
+
+/// An explosion waiting to be resolved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct PendingExplosion {
+    /// The properties of the explosive that exploded.
+    pub(crate) explosive: Explosive,
+    /// The global center of the explosion.
+    pub(crate) point: Vec3,
+    /// The entity responsible for the explosion.
+    pub(crate) instigator: Option<Entity>,
+    /// The explosive that started the chain reaction this explosion is part of.
+    pub(crate) chain: Entity,
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn resolve_explosions(
+    mut pending_explosions: ResMut<PendingExplosions>,
+    mut explosion_helper: ExplosionHelper,
+) {
+    let explosions = std::mem::take(&mut pending_explosions.0);
+    explosion_helper.apply_explosions(&explosions);
 }
 
 /// A [`SystemParam`] for applying explosions in the world.
 #[derive(SystemParam)]
 pub(crate) struct ExplosionHelper<'w, 's> {
-    collider_query: Query<'w, 's, (&'static Collider, &'static GlobalTransform)>,
-    collider_of_query: Query<'w, 's, &'static ColliderOf>,
+    collider_query: Query<
+        'w,
+        's,
+        (
+            &'static Collider,
+            &'static GlobalTransform,
+            &'static ColliderOf,
+        ),
+    >,
     body_query: Query<
         'w,
         's,
@@ -253,59 +359,70 @@ pub(crate) struct ExplosionHelper<'w, 's> {
             &'static mut LinearVelocity,
             &'static mut AngularVelocity,
             &'static ComputedCenterOfMass,
-            &'static RigidBodyColliders,
         ),
     >,
     damageable_query: Query<'w, 's, Has<Player>, With<Health>>,
+    weapon_stats: Query<'w, 's, &'static WeaponStats, With<Player>>,
+    player_rules: Res<'w, PlayerExplosionRules>,
     spatial_query: SpatialQuery<'w, 's>,
     commands: Commands<'w, 's>,
 }
 
+/// The accumulated effect of all explosions of a batch on a single body.
+#[derive(Debug, Default)]
+struct ExplosionHit {
+    damage: f32,
+    lin_impulse: Vec3,
+    ang_impulse: Vec3,
+    /// The first explosion that damaged the body, used to attribute the damage.
+    cause: Option<PendingExplosion>,
+}
+
 impl ExplosionHelper<'_, '_> {
-    /// Applies an explosion to all entities within the explosion radius at the given point.
+    /// Applies a batch of explosions to the world.
     ///
-    /// This also triggers the [`OnExplode`] event for any explosive entities hit by the explosion.
-    pub(crate) fn apply_explosion(&mut self, explosive: &Explosive, point: Vec3) {
-        // Query for all collider entities of characters and props within the explosion radius.
-        let shape = Collider::sphere(explosive.radius);
-        let filter = SpatialQueryFilter::default();
-        let hit_entities =
-            self.spatial_query
-                .shape_intersections(&shape, point, Quat::IDENTITY, &filter);
-
-        // Get the bodies of the hit entities.
-        let mut hit_bodies = self
-            .collider_of_query
-            .iter_many(hit_entities)
-            .map(|&ColliderOf { body }| body)
-            .collect::<Vec<Entity>>();
-
-        // Deduplicate the hit bodies to avoid applying multiple impulses to the same body
-        // in case of multiple colliders.
-        hit_bodies.sort();
-        hit_bodies.dedup();
-
-        // Apply the explosion impulse to each hit body.
-        for body in hit_bodies {
-            // Get the body's transform, velocity, center of mass, and attached colliders.
-            let Ok((rb, transform, mut lin_vel, mut ang_vel, local_com, colliders)) =
-                self.body_query.get_mut(body)
-            else {
-                continue;
-            };
-            let global_com = transform.translation() + transform.rotation() * local_com.0;
+    /// Overlapping explosions are grouped into clusters that share a single spatial query,
+    /// and the impulses and damage of all explosions are accumulated per body before being applied.
+    pub(crate) fn apply_explosions(&mut self, explosions: &[PendingExplosion]) {
+        if explosions.is_empty() {
+            return;
+        }
 
-            // If the entity has health, we apply damage to it.
-            if let Ok(is_player) = self.damageable_query.get(body) {
-                let mut damage = explosive.damage;
+        let mut hits = HashMap::<Entity, ExplosionHit>::new();
+        for cluster in cluster_explosions(explosions) {
+            self.accumulate_cluster(&cluster, &mut hits);
+        }
 
-                if is_player {
-                    // If the explosive damages the player, we apply a scaled damage immediately.
-                    if !explosive.damages_player {
-                        continue;
+        // Sort the bodies so that the order in which damage is dealt is deterministic.
+        let mut hits = hits.into_iter().collect::<Vec<_>>();
+        hits.sort_by_key(|(body, _)| *body);
+
+        for (body, hit) in hits {
+            // If the entity has health, we apply damage to it.
+            let is_player = self.damageable_query.get(body).unwrap_or(false);
+            // Explosions that don't damage the player still shove them, but deal no damage.
+            if self.damageable_query.contains(body) && hit.cause.is_some() {
+                let mut damage = OnDamage::new(hit.damage, DamageKind::Explosion)
+                    .in_direction(Dir3::new(hit.lin_impulse).ok());
+                if let Some(cause) = hit.cause {
+                    damage = damage
+                        .with_instigator(cause.instigator)
+                        .at(cause.point)
+                        .with_source(DamageSource::Chain(cause.chain))
+                        .non_lethal(
+                            is_player
+                                && cause.instigator == Some(body)
+                                && !self.player_rules.lethal_self_damage,
+                        );
+                    if let Some(status_effect) = cause.explosive.status_effect {
+                        self.commands.entity(body).trigger(
+                            OnStatusEffect::new(status_effect).with_instigator(cause.instigator),
+                        );
                     }
-                    damage *= EXPLOSION_PLAYER_DAMAGE_SCALE;
-                    self.commands.entity(body).trigger(OnDamage(damage));
+                }
+                if is_player {
+                    // Damage against the player is applied immediately.
+                    self.commands.entity(body).trigger(damage);
                 } else {
                     // For damage against enemies or explosives, we use a small delay.
                     let delay = 0.2;
@@ -314,46 +431,296 @@ impl ExplosionHelper<'_, '_> {
                         .try_insert_if_new(AutoTimer(Timer::from_seconds(delay, TimerMode::Once)))
                         .observe(
                             move |trigger: Trigger<OnAutoTimerFinish>, mut commands: Commands| {
-                                commands.entity(trigger.target()).trigger(OnDamage(damage));
+                                commands.entity(trigger.target()).trigger(damage);
                             },
                         );
                 }
             }
 
+            if is_player {
+                // The player's rotation is locked, so only the linear part matters.
+                let shove = hit
+                    .lin_impulse
+                    .clamp_length_max(self.player_rules.max_knockback);
+                if shove != Vec3::ZERO {
+                    self.commands.entity(body).trigger(OnKnockback { shove });
+                }
+                continue;
+            }
+
+            let Ok((rb, _, mut lin_vel, mut ang_vel, _)) = self.body_query.get_mut(body) else {
+                continue;
+            };
             if !rb.is_dynamic() {
                 continue;
             }
 
-            // Iterate over the colliders of the body to find the point closest
-            // to the source of the explosion.
-            let (closest_point, _is_inside) = self
-                .collider_query
-                .iter_many(colliders.iter())
-                .map(|(collider, transform)| {
-                    collider.project_point(
-                        transform.translation(),
-                        transform.rotation(),
-                        point,
-                        true,
-                    )
-                })
-                .min_by(|(a, _), (b, _)| {
-                    a.distance_squared(point)
-                        .partial_cmp(&b.distance_squared(point))
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                })
-                .expect("Body hit by explosion has no colliders. Huh???");
+            // Apply the accumulated impulses to the body's velocities.
+            lin_vel.0 += hit.lin_impulse;
+            ang_vel.0 += hit.ang_impulse;
+        }
+    }
+
+    /// Finds all bodies hit by a cluster of overlapping explosions
+    /// and accumulates the damage and impulses they receive.
+    fn accumulate_cluster(
+        &self,
+        cluster: &ExplosionCluster,
+        hits: &mut HashMap<Entity, ExplosionHit>,
+    ) {
+        // Query for all colliders within a sphere that bounds every explosion of the cluster.
+        let shape = Collider::sphere(cluster.radius);
+        let filter = SpatialQueryFilter::default();
+        let hit_colliders =
+            self.spatial_query
+                .shape_intersections(&shape, cluster.center, Quat::IDENTITY, &filter);
+        let cluster = &cluster.explosions;
+
+        // For every explosion, find the point closest to its center on each body it hits.
+        // Bodies can have multiple colliders, so we keep the closest point across all of them.
+        let mut closest_points = HashMap::<(Entity, usize), Vec3>::new();
+        for (collider, transform, &ColliderOf { body }) in
+            self.collider_query.iter_many(hit_colliders)
+        {
+            for (i, explosion) in cluster.iter().enumerate() {
+                let (closest_point, is_inside) = collider.project_point(
+                    transform.translation(),
+                    transform.rotation(),
+                    explosion.point,
+                    true,
+                );
+                let distance_squared = closest_point.distance_squared(explosion.point);
+                if !is_inside && distance_squared > explosion.explosive.radius.powi(2) {
+                    continue;
+                }
+                closest_points
+                    .entry((body, i))
+                    .and_modify(|point| {
+                        if distance_squared < point.distance_squared(explosion.point) {
+                            *point = closest_point;
+                        }
+                    })
+                    .or_insert(closest_point);
+            }
+        }
+
+        for ((body, i), closest_point) in closest_points {
+            let explosion = &cluster[i];
+            let explosive = &explosion.explosive;
+
+            let is_player = self.damageable_query.get(body).unwrap_or(false);
+            let self_inflicted = is_player && explosion.instigator == Some(body);
+            let weapon_stats = self.weapon_stats.get(body).ok();
+
+            let hit = hits.entry(body).or_default();
+            if !is_player || explosive.damages_player {
+                // Damage against the player is scaled down further by its `DamageMultipliers`.
+                let mut damage = explosive.damage;
+                if self_inflicted {
+                    damage *= self.player_rules.self_damage_scale
+                        * weapon_stats.map_or(1.0, |stats| stats.self_blast_damage);
+                }
+                hit.damage += damage;
+                hit.cause.get_or_insert(*explosion);
+            }
+
+            let Ok((_, transform, _, _, local_com)) = self.body_query.get(body) else {
+                continue;
+            };
+            let global_com = transform.translation() + transform.rotation() * local_com.0;
 
             // Compute the impulse direction and magnitude.
             // We ignore mass properties here to make explosions more predictable and fun.
             // TODO: We could support a falloff based on the distance from the center of the explosion.
-            let explosion_direction = (closest_point - point).normalize_or_zero();
-            let lin_impulse = explosive.impulse_strength * explosion_direction;
-            let ang_impulse = (closest_point - global_com).cross(lin_impulse);
+            let explosion_direction = (closest_point - explosion.point).normalize_or_zero();
+            let mut lin_impulse = explosive.impulse_strength * explosion_direction;
+            if is_player {
+                lin_impulse *= self.player_rules.knockback_scale;
+                if self_inflicted {
+                    lin_impulse *= weapon_stats.map_or(1.0, |stats| stats.blast_jump);
+                }
+            }
+            hit.lin_impulse += lin_impulse;
+            hit.ang_impulse += (closest_point - global_com).cross(lin_impulse);
+        }
+    }
+}
+
+/// The most explosions that can share a single spatial query.
+const MAX_CLUSTER_SIZE: usize = 16;
+
+/// How much larger than its largest explosion the bounding sphere of a cluster may grow.
+///
+/// Every collider in the bounding sphere is tested against every explosion of the cluster,
+/// so letting a cluster grow along a long chain reaction would cost more than querying each explosion on its own.
+const MAX_CLUSTER_RADIUS_SCALE: f32 = 1.5;
+
+/// Overlapping explosions that are resolved with a single spatial query.
+#[derive(Debug)]
+struct ExplosionCluster {
+    explosions: Vec<PendingExplosion>,
+    /// The center of the sphere bounding every explosion of the cluster.
+    center: Vec3,
+    /// The radius of the sphere bounding every explosion of the cluster.
+    radius: f32,
+}
+
+impl ExplosionCluster {
+    fn new(explosion: PendingExplosion) -> Self {
+        Self {
+            explosions: vec![explosion],
+            center: explosion.point,
+            radius: explosion.explosive.radius,
+        }
+    }
+
+    /// Adds the explosion to the cluster if the cluster stays small enough.
+    fn try_add(&mut self, explosion: &PendingExplosion) -> bool {
+        if self.explosions.len() >= MAX_CLUSTER_SIZE {
+            return false;
+        }
+        let count = self.explosions.len() as f32;
+        let center = (self.center * count + explosion.point) / (count + 1.0);
+        let members = || self.explosions.iter().chain([explosion]);
+        let radius = members()
+            .map(|e| e.point.distance(center) + e.explosive.radius)
+            .fold(0.0, f32::max);
+        let largest_radius = members().map(|e| e.explosive.radius).fold(0.0, f32::max);
+        if radius > largest_radius * MAX_CLUSTER_RADIUS_SCALE {
+            return false;
+        }
+        self.explosions.push(*explosion);
+        self.center = center;
+        self.radius = radius;
+        true
+    }
+}
+
+/// Groups explosions into clusters of overlapping spheres.
+///
+/// Clusters are capped in size and extent, so a long chain reaction is split into
+/// several clusters instead of being merged into one huge query.
+fn cluster_explosions(explosions: &[PendingExplosion]) -> Vec<ExplosionCluster> {
+    let mut clusters = Vec::<ExplosionCluster>::new();
+    for explosion in explosions {
+        if !clusters
+            .iter_mut()
+            .any(|cluster| cluster.try_add(explosion))
+        {
+            clusters.push(ExplosionCluster::new(*explosion));
+        }
+    }
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    fn explosion_at(point: Vec3) -> PendingExplosion {
+        PendingExplosion {
+            explosive: Explosive::default(),
+            point,
+            instigator: None,
+            chain: Entity::PLACEHOLDER,
+        }
+    }
+
+    #[test]
+    fn overlapping_explosions_share_a_cluster() {
+        let explosions = [
+            explosion_at(Vec3::ZERO),
+            explosion_at(Vec3::X * 0.5),
+            explosion_at(Vec3::Z * 0.5),
+            explosion_at(Vec3::X * 100.0),
+        ];
+        let clusters = cluster_explosions(&explosions);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].explosions.len(), 3);
+    }
+
+    #[test]
+    fn chain_reactions_are_split_into_bounded_clusters() {
+        // A line of barrels where each one only overlaps its neighbors.
+        let explosions = (0..30)
+            .map(|i| explosion_at(Vec3::X * i as f32 * 1.5))
+            .collect::<Vec<_>>();
+        let clusters = cluster_explosions(&explosions);
+        assert!(clusters.len() > 1);
+        assert_eq!(
+            clusters.iter().map(|c| c.explosions.len()).sum::<usize>(),
+            explosions.len()
+        );
+        let max_radius = Explosive::default().radius * MAX_CLUSTER_RADIUS_SCALE;
+        for cluster in &clusters {
+            assert!(cluster.explosions.len() <= MAX_CLUSTER_SIZE);
+            assert!(cluster.radius <= max_radius);
+        }
+    }
+
+    /// Sets off a chain of explosives in a headless physics world and resolves them in one batch.
+    #[test]
+    fn resolve_chain_reaction_in_one_batch() {
+        const BARRELS: usize = 30;
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            PhysicsPlugins::default(),
+        ));
+        app.init_asset::<Mesh>();
+        app.insert_resource(Gravity::ZERO);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+        app.init_resource::<PendingExplosions>();
+        app.init_resource::<PlayerExplosionRules>();
+        app.add_systems(
+            Update,
+            resolve_explosions.run_if(|pending: Res<PendingExplosions>| !pending.is_empty()),
+        );
+        app.finish();
+        app.cleanup();
+
+        let barrels = (0..BARRELS)
+            .map(|i| {
+                app.world_mut()
+                    .spawn((
+                        RigidBody::Dynamic,
+                        Collider::cylinder(0.4, 1.0),
+                        Transform::from_xyz(i as f32 * 1.5, 0.0, 0.0),
+                    ))
+                    .id()
+            })
+            .collect::<Vec<_>>();
+        // Let the physics world pick up the new colliders.
+        for _ in 0..3 {
+            app.update();
+        }
 
-            // Apply the impulses to the body's velocities.
-            lin_vel.0 += lin_impulse;
-            ang_vel.0 += ang_impulse;
+        // Every barrel explodes right below itself in the same tick.
+        let explosions = (0..BARRELS)
+            .map(|i| explosion_at(Vec3::new(i as f32 * 1.5, -1.0, 0.0)))
+            .collect::<Vec<_>>();
+        // Every cluster costs one spatial query, so neighboring barrels must share them.
+        // With this spacing, three barrels fit into a cluster.
+        assert_eq!(cluster_explosions(&explosions).len(), BARRELS / 3);
+        app.world_mut()
+            .resource_mut::<PendingExplosions>()
+            .extend(explosions);
+
+        app.update();
+
+        assert!(app.world().resource::<PendingExplosions>().is_empty());
+        for barrel in barrels {
+            let velocity = app.world().get::<LinearVelocity>(barrel).unwrap();
+            assert!(velocity.y > 0.0, "barrel {barrel:?} was not launched");
         }
     }
 }
@@ -1,11 +1,14 @@
-use std::time::Duration;
+use std::{collections::VecDeque, time::Duration};
 
 use bevy::prelude::*;
 use bevy_mesh_decal::Decal;
 
 use crate::{
     despawn_after::{DespawnAfter, FadeOutAndDespawn},
-    gameplay::{npc::lifecycle::Gib, waves::Waves},
+    gameplay::{
+        npc::{lifecycle::Gib, ragdoll::Ragdoll},
+        waves::Waves,
+    },
     menus::Menu,
     screens::Screen,
 };
@@ -13,20 +16,38 @@ use crate::{
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<GoreSettings>();
     app.init_resource::<GoreSettings>();
+    app.init_resource::<DecalHistory>();
 
     app.add_systems(
         Update,
-        (despawn_decals, despawn_gibs)
+        (
+            despawn_decals,
+            limit_decals,
+            despawn_gore::<Gib>,
+            despawn_gore::<Ragdoll>,
+        )
             .run_if(in_state(Screen::Gameplay).and(not(in_state(Menu::Pause)))),
     );
+    app.add_observer(remember_decal);
 }
 
+/// How many decals may exist at once, whatever the gore settings say.
+/// Every shotgun blast sprays a few bullet holes, so they would pile up otherwise.
+const MAX_DECALS: usize = 200;
+
+/// The decals that are around, oldest first.
+#[derive(Resource, Default)]
+struct DecalHistory(VecDeque<Entity>);
+
 #[derive(Resource, Reflect, Clone)]
 #[reflect(Resource)]
 pub(crate) struct GoreSettings {
     pub blood_decals: Gore,
     pub gibs: Gore,
     pub gib_count: u8,
+    pub death_style: DeathStyle,
+    /// How many ragdolls may lie around at once. The oldest ones fade out first.
+    pub ragdoll_count: u8,
 }
 
 impl Default for GoreSettings {
@@ -41,10 +62,22 @@ impl Default for GoreSettings {
             #[cfg(feature = "native")]
             gibs: Gore::DespawnAfterWave,
             gib_count: 5,
+            death_style: DeathStyle::Gibs,
+            ragdoll_count: 8,
         }
     }
 }
 
+/// What is left of an NPC when it dies. Only used when gibs are enabled.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeathStyle {
+    /// The NPC bursts into body parts.
+    #[default]
+    Gibs,
+    /// The NPC's body collapses as a physics ragdoll.
+    Ragdoll,
+}
+
 #[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Gore {
     None,
@@ -99,11 +132,33 @@ fn despawn_decals(
     Ok(())
 }
 
-fn despawn_gibs(
+fn remember_decal(trigger: Trigger<OnAdd, Decal>, mut history: ResMut<DecalHistory>) {
+    history.0.push_back(trigger.target());
+}
+
+/// Despawns the oldest decals once there are more than [`MAX_DECALS`].
+fn limit_decals(
+    mut history: ResMut<DecalHistory>,
+    decals: Query<(), (With<Decal>, Without<FadeOutAndDespawn>)>,
+    mut commands: Commands,
+) {
+    // Forget the decals that are gone or already fading out.
+    history.0.retain(|decal| decals.contains(*decal));
+    while history.0.len() > MAX_DECALS {
+        let Some(oldest) = history.0.pop_front() else {
+            break;
+        };
+        // Fading out would fade every decal sharing the material, like all bullet holes in stone.
+        commands.entity(oldest).try_despawn();
+    }
+}
+
+/// Applies the gib settings to gibs and other body remains such as ragdolls.
+fn despawn_gore<T: Component>(
     mut commands: Commands,
-    gibs: Query<(Entity, Ref<Gib>)>,
-    gibs_with_despawn_timer: Query<Entity, (With<Gib>, With<DespawnAfter>)>,
-    gibs_without_fadeout_timer: Query<Entity, (With<Gib>, Without<FadeOutAndDespawn>)>,
+    gibs: Query<(Entity, Ref<T>)>,
+    gibs_with_despawn_timer: Query<Entity, (With<T>, With<DespawnAfter>)>,
+    gibs_without_fadeout_timer: Query<Entity, (With<T>, Without<FadeOutAndDespawn>)>,
     waves: Query<&Waves>,
     gore_settings: Res<GoreSettings>,
 ) -> Result {
@@ -1,19 +1,35 @@
 use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use bevy_trenchbroom::prelude::*;
 
-use crate::{PostPhysicsAppSystems, screens::Screen};
+use crate::{PostPhysicsAppSystems, screens::Screen, third_party::bevy_trenchbroom::Worldspawn};
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<Health>();
+    app.register_type::<(Health, DamageMultipliers, DamageKind)>();
+    app.register_type::<(Armor, Shield, Regeneration, Invulnerable)>();
     app.add_systems(
         Update,
         kill_out_of_bounds
             .in_set(PostPhysicsAppSystems::TriggerDeath)
             .run_if(in_state(Screen::Gameplay)),
     );
+    app.add_systems(
+        Update,
+        (recharge_shields, regenerate_health)
+            .in_set(PostPhysicsAppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        tick_invulnerability
+            .in_set(PostPhysicsAppSystems::TickTimers)
+            .run_if(in_state(Screen::Gameplay)),
+    );
     app.add_observer(on_damage);
 }
 
-#[derive(Component, Debug, Reflect)]
+#[derive(Component, Debug, Clone, Reflect)]
 #[reflect(Component)]
 pub(crate) struct Health {
     pub(crate) current: f32,
@@ -33,6 +49,10 @@ impl Health {
         self.current = self.max;
     }
 
+    pub(crate) fn heal(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
     fn damage(&mut self, amount: f32) {
         self.current -= amount;
         self.current = self.current.max(0.0);
@@ -52,27 +72,356 @@ impl Default for Health {
     }
 }
 
-fn on_damage(trigger: Trigger<OnDamage>, mut health: Query<&mut Health>, mut commands: Commands) {
+/// An armor layer that absorbs a share of incoming damage until it is depleted.
+/// Armor does not recharge on its own.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Armor {
+    pub(crate) current: f32,
+    pub(crate) max: f32,
+    /// The share of damage absorbed by the armor, between 0 and 1.
+    pub(crate) absorption: f32,
+}
+
+impl Armor {
+    pub(crate) fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            absorption: 0.5,
+        }
+    }
+
+    /// Absorbs part of the damage and returns the rest.
+    fn absorb(&mut self, amount: f32) -> f32 {
+        let absorbed = (amount * self.absorption).min(self.current);
+        self.current -= absorbed;
+        amount - absorbed
+    }
+}
+
+/// A shield that absorbs all damage until it is depleted,
+/// and recharges after not taking damage for a while.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Shield {
+    pub(crate) current: f32,
+    pub(crate) max: f32,
+    pub(crate) recharge_per_second: f32,
+    /// Restarted whenever the shield owner takes damage.
+    pub(crate) recharge_delay: Timer,
+}
+
+impl Shield {
+    pub(crate) fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            recharge_per_second: 10.0,
+            recharge_delay: Timer::from_seconds(3.0, TimerMode::Once),
+        }
+    }
+
+    /// Absorbs as much of the damage as possible and returns the rest.
+    fn absorb(&mut self, amount: f32) -> f32 {
+        self.recharge_delay.reset();
+        let absorbed = amount.min(self.current);
+        self.current -= absorbed;
+        amount - absorbed
+    }
+}
+
+/// Slowly regenerates [`Health`] up to a fraction of the maximum.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Regeneration {
+    pub(crate) per_second: f32,
+    /// Health is not regenerated past this fraction of the maximum.
+    pub(crate) max_fraction: f32,
+}
+
+impl Default for Regeneration {
+    fn default() -> Self {
+        Self {
+            per_second: 0.0,
+            max_fraction: 0.5,
+        }
+    }
+}
+
+/// Ignores all damage until the timer runs out, e.g. right after the player respawned.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Invulnerable(pub(crate) Timer);
+
+impl Invulnerable {
+    pub(crate) fn from_seconds(secs: f32) -> Self {
+        Self(Timer::from_seconds(secs, TimerMode::Once))
+    }
+}
+
+fn on_damage(
+    trigger: Trigger<OnDamage>,
+    mut health: Query<
+        (
+            &mut Health,
+            Option<&DamageMultipliers>,
+            Option<&mut Shield>,
+            Option<&mut Armor>,
+        ),
+        Without<Invulnerable>,
+    >,
+    mut commands: Commands,
+) {
     let entity = trigger.target();
-    let Ok(mut health) = health.get_mut(entity) else {
+    let Ok((mut health, multipliers, shield, armor)) = health.get_mut(entity) else {
         return;
     };
-    health.damage(trigger.event().0);
+    let damage = trigger.event();
+    let multiplier = multipliers.map_or(1.0, |m| m.multiplier(damage.kind));
+    let mut amount = damage.amount * multiplier;
+    // The shield takes the hit first, then the armor absorbs its share of the rest.
+    if let Some(mut shield) = shield {
+        amount = shield.absorb(amount);
+    }
+    if let Some(mut armor) = armor {
+        amount = armor.absorb(amount);
+    }
+    if damage.non_lethal {
+        amount = amount.min(health.current - 1.0).max(0.0);
+    }
+    health.damage(amount);
+    commands.entity(entity).trigger(OnDamageTaken {
+        damage: *damage,
+        health_lost: amount,
+    });
     if health.is_dead() {
-        commands.entity(entity).remove::<Health>().trigger(OnDeath);
+        commands
+            .entity(entity)
+            .remove::<Health>()
+            .trigger(OnDeath::from_damage(damage));
     }
 }
 
-#[derive(Debug, Event)]
-pub(crate) struct OnDamage(pub(crate) f32);
+/// The kind of damage dealt by an [`OnDamage`] event.
+/// Derives [`FgdType`] so that map hazards can choose it in TrenchBroom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, FgdType)]
+pub(crate) enum DamageKind {
+    #[default]
+    Generic,
+    /// A pellet shot by the player's weapon.
+    Pellet,
+    /// A melee hit by an NPC.
+    Melee,
+    /// A projectile fired by an NPC.
+    Projectile,
+    Explosion,
+    Fall,
+    /// Damage over time from the burning status effect.
+    Burning,
+    /// Damage over time from the bleeding status effect.
+    Bleeding,
+    /// Falling out of the level.
+    OutOfBounds,
+}
 
-#[derive(Debug, Event)]
-pub(crate) struct OnDeath;
+/// Identifies what dealt a particular instance of damage beyond its [`DamageKind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum DamageSource {
+    /// A chain of explosions, identified by the explosive that started it.
+    Chain(Entity),
+    /// A weapon of the player.
+    Weapon(WeaponId),
+}
+
+/// Identifies a weapon. We only have the shotgun for now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub(crate) struct WeaponId(pub(crate) u32);
+
+/// An event that is triggered on an entity to damage it.
+#[derive(Debug, Event, Clone, Copy)]
+pub(crate) struct OnDamage {
+    /// The amount of damage before per-entity [`DamageMultipliers`] are applied.
+    pub(crate) amount: f32,
+    pub(crate) kind: DamageKind,
+    /// The entity responsible for the damage, e.g. the player that shot a barrel.
+    pub(crate) instigator: Option<Entity>,
+    /// The point in world space where the damage was dealt.
+    pub(crate) point: Option<Vec3>,
+    /// The direction the damage was travelling in.
+    pub(crate) direction: Option<Dir3>,
+    pub(crate) source: Option<DamageSource>,
+    /// Whether the damage leaves the target with at least 1 health instead of killing it.
+    pub(crate) non_lethal: bool,
+}
+
+impl OnDamage {
+    pub(crate) fn new(amount: f32, kind: DamageKind) -> Self {
+        Self {
+            amount,
+            kind,
+            instigator: None,
+            point: None,
+            direction: None,
+            source: None,
+            non_lethal: false,
+        }
+    }
+
+    pub(crate) fn with_instigator(mut self, instigator: impl Into<Option<Entity>>) -> Self {
+        self.instigator = instigator.into();
+        self
+    }
+
+    pub(crate) fn at(mut self, point: Vec3) -> Self {
+        self.point = Some(point);
+        self
+    }
+
+    pub(crate) fn in_direction(mut self, direction: impl Into<Option<Dir3>>) -> Self {
+        self.direction = direction.into();
+        self
+    }
+
+    pub(crate) fn with_source(mut self, source: impl Into<Option<DamageSource>>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    pub(crate) fn non_lethal(mut self, non_lethal: bool) -> Self {
+        self.non_lethal = non_lethal;
+        self
+    }
+}
+
+/// An event that is triggered on an entity after an [`OnDamage`] was applied to its [`Health`].
+#[derive(Debug, Event, Clone, Copy)]
+pub(crate) struct OnDamageTaken {
+    pub(crate) damage: OnDamage,
+    /// The health actually lost after [`DamageMultipliers`], [`Shield`] and [`Armor`].
+    pub(crate) health_lost: f32,
+}
+
+/// An event that is triggered on an entity when its [`Health`] drops to zero.
+#[derive(Debug, Event, Clone, Copy)]
+pub(crate) struct OnDeath {
+    /// The entity that got the kill, if any.
+    pub(crate) killer: Option<Entity>,
+    /// The kind of damage that dealt the killing blow.
+    pub(crate) kind: DamageKind,
+    pub(crate) source: Option<DamageSource>,
+    /// The point in world space where the killing blow landed.
+    pub(crate) point: Option<Vec3>,
+    /// The direction the killing blow was travelling in.
+    pub(crate) direction: Option<Dir3>,
+}
+
+impl OnDeath {
+    pub(crate) fn new(kind: DamageKind) -> Self {
+        Self {
+            killer: None,
+            kind,
+            source: None,
+            point: None,
+            direction: None,
+        }
+    }
+
+    pub(crate) fn from_damage(damage: &OnDamage) -> Self {
+        Self {
+            killer: damage.instigator,
+            kind: damage.kind,
+            source: damage.source,
+            point: damage.point,
+            direction: damage.direction,
+        }
+    }
+}
+
+/// Scales incoming damage per [`DamageKind`] before it is subtracted from [`Health`].
+/// A multiplier below 1 acts as a resistance, one above 1 as a weakness.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) struct DamageMultipliers(Vec<(DamageKind, f32)>);
+
+impl DamageMultipliers {
+    pub(crate) fn with(mut self, kind: DamageKind, multiplier: f32) -> Self {
+        self.set(kind, multiplier);
+        self
+    }
+
+    pub(crate) fn set(&mut self, kind: DamageKind, multiplier: f32) {
+        if let Some((_, existing)) = self.0.iter_mut().find(|(k, _)| *k == kind) {
+            *existing = multiplier;
+        } else {
+            self.0.push((kind, multiplier));
+        }
+    }
+
+    pub(crate) fn multiplier(&self, kind: DamageKind) -> f32 {
+        self.0
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map_or(1.0, |(_, multiplier)| *multiplier)
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn recharge_shields(mut shields: Query<&mut Shield, With<Health>>, time: Res<Time>) {
+    for mut shield in &mut shields {
+        if shield.current >= shield.max {
+            continue;
+        }
+        shield.recharge_delay.tick(time.delta());
+        if !shield.recharge_delay.finished() {
+            continue;
+        }
+        shield.current =
+            (shield.current + shield.recharge_per_second * time.delta_secs()).min(shield.max);
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn regenerate_health(mut health: Query<(&mut Health, &Regeneration)>, time: Res<Time>) {
+    for (mut health, regeneration) in &mut health {
+        let cap = health.max * regeneration.max_fraction;
+        if health.current >= cap || regeneration.per_second <= 0.0 {
+            continue;
+        }
+        let amount = (regeneration.per_second * time.delta_secs()).min(cap - health.current);
+        health.heal(amount);
+    }
+}
+
+fn tick_invulnerability(
+    mut invulnerable: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut invulnerable) in &mut invulnerable {
+        invulnerable.0.tick(time.delta());
+        if invulnerable.0.finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
 
-fn kill_out_of_bounds(health: Query<(Entity, &Transform)>, mut commands: Commands) {
+fn kill_out_of_bounds(
+    health: Query<(Entity, &Transform), With<Health>>,
+    worldspawn: Option<Single<&Worldspawn>>,
+    mut commands: Commands,
+) {
+    // The level might not be loaded yet, so fall back to the default kill plane.
+    let kill_plane_height = worldspawn.map_or(Worldspawn::default().kill_plane_height, |w| {
+        w.kill_plane_height
+    });
     for (entity, transform) in health.iter() {
-        if transform.translation.y < -300.0 {
-            commands.entity(entity).trigger(OnDeath);
+        if transform.translation.y < kill_plane_height {
+            // Removing the health makes sure we only trigger the death once.
+            commands
+                .entity(entity)
+                .remove::<Health>()
+                .trigger(OnDeath::new(DamageKind::OutOfBounds));
         }
     }
 }
@@ -5,9 +5,14 @@ use bevy_simple_subsecond_system::hot;
 
 use crate::asset_tracking::LoadResource;
 use crate::font::FontAssets;
-use crate::gameplay::health::{Health, OnDeath};
+use crate::gameplay::health::{Armor, Health, OnDeath, Shield};
 use crate::gameplay::npc::Npc;
 use crate::gameplay::player::Player;
+use crate::gameplay::player::bindings::{Bindings, Control};
+use crate::gameplay::player::gunplay::Ammo;
+use crate::gameplay::player::movement::{MovementStats, Stamina};
+use crate::gameplay::respawn::Lives;
+use crate::gameplay::status_effects::{StatusEffect, StatusEffectKind, StatusEffects};
 use crate::gameplay::upgrades::Upgrades;
 use crate::gameplay::waves::{
     GameMode, WaveAdvanced, WaveFinishedPreparing, WaveStartedPreparing, Waves,
@@ -19,19 +24,35 @@ pub(super) fn plugin(app: &mut App) {
     app.load_resource::<HudAssets>();
     app.add_systems(
         OnEnter(Screen::Gameplay),
-        (spawn_health_bar, spawn_wave_hud),
+        (
+            spawn_health_bar,
+            spawn_stamina_bar,
+            spawn_ammo_text,
+            spawn_status_effect_hud,
+            spawn_wave_hud,
+        ),
     );
     app.add_systems(
         Update,
         (
             update_health_bar,
+            update_stamina_bar,
+            update_ammo_text,
+            update_status_effect_hud,
             update_prep_time_text,
             update_wave_text,
+            update_lives_text,
             blink_upgrade_menu_text,
         ),
     );
     app.register_type::<HealthBar>();
+    app.register_type::<ArmorBar>();
+    app.register_type::<ShieldBar>();
+    app.register_type::<StaminaBar>();
+    app.register_type::<AmmoText>();
+    app.register_type::<StatusEffectHud>();
     app.register_type::<WaveText>();
+    app.register_type::<LivesText>();
     app.add_observer(add_angry_icon);
     app.add_observer(add_dead_icon);
     app.add_observer(flush_on_wave_advanced);
@@ -79,10 +100,38 @@ impl FromWorld for HudAssets {
 #[reflect(Component)]
 pub(crate) struct HealthBar;
 
+/// The segment of the health bar showing the player's [`Armor`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct ArmorBar;
+
+/// The segment of the health bar showing the player's [`Shield`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct ShieldBar;
+
+/// The bar below the health bar showing the player's [`Stamina`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct StaminaBar;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct AmmoText;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct StatusEffectHud;
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub(crate) struct WaveText;
 
+/// Only shows anything when lives are enabled.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct LivesText;
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub(crate) struct WaveIconParent;
@@ -130,6 +179,12 @@ fn spawn_wave_hud(mut commands: Commands, fonts: Res<FontAssets>, game_mode: Res
                 TextFont::from_font_size(26.0).with_font(fonts.default.clone()),
                 WaveText
             ),
+            (
+                Text::new(""),
+                TextFont::from_font_size(20.0).with_font(fonts.default.clone()),
+                TextColor(palette::LABEL_TEXT),
+                LivesText
+            ),
             (
                 Node {
                     width: Percent(300.0),
@@ -234,12 +289,21 @@ fn update_wave_text(
     }
 }
 
+fn update_lives_text(
+    lives: Single<Option<&Lives>, With<Player>>,
+    mut lives_text: Single<&mut Text, With<LivesText>>,
+) {
+    ***lives_text = lives.map_or(String::new(), |lives| format!("Lives: {}", lives.0));
+}
+
 fn spawn_prep_icon(
     _trigger: Trigger<WaveStartedPreparing>,
     container: Single<Entity, With<WaveIconParent>>,
     fonts: Res<FontAssets>,
+    bindings: Res<Bindings>,
     mut commands: Commands,
 ) {
+    let upgrade_key = bindings.get(Control::OpenUpgradeMenu).keyboard.label();
     commands.entity(*container).with_children(|parent| {
         parent.spawn((
             Node {
@@ -260,7 +324,7 @@ fn spawn_prep_icon(
                         margin: UiRect::top(Px(10.0)),
                         ..default()
                     },
-                    Text::new("Press F to upgrade!"),
+                    Text::new(format!("Press {upgrade_key} to upgrade!")),
                     TextFont::default()
                         .with_font_size(26.0)
                         .with_font(fonts.default.clone()),
@@ -345,41 +409,236 @@ fn spawn_health_bar(
             },
             BorderRadius::MAX,
             BackgroundColor(Color::from(tailwind::ZINC_900.with_alpha(0.8))),
-            children![(
-                HealthBar,
-                Node {
-                    width: Percent(hp * 100.0),
-                    height: Percent(100.0),
-                    overflow: Overflow::clip(),
-                    ..default()
-                },
-                BorderRadius::all(Px(10.0)),
-                BackgroundColor(Color::from(tailwind::RED_600.with_alpha(0.5))),
-                children![(
-                    ImageNode {
-                        color: tailwind::RED_400.with_alpha(0.75).into(),
-                        image: hud_assets.health_bar_texture.clone(),
-                        image_mode: NodeImageMode::Auto,
+            children![
+                (
+                    HealthBar,
+                    Node {
+                        width: Percent(hp * 100.0),
+                        height: Percent(100.0),
+                        overflow: Overflow::clip(),
                         ..default()
                     },
+                    BorderRadius::all(Px(10.0)),
+                    BackgroundColor(Color::from(tailwind::RED_600.with_alpha(0.5))),
+                    children![(
+                        ImageNode {
+                            color: tailwind::RED_400.with_alpha(0.75).into(),
+                            image: hud_assets.health_bar_texture.clone(),
+                            image_mode: NodeImageMode::Auto,
+                            ..default()
+                        },
+                        Node {
+                            position_type: PositionType::Absolute,
+                            top: Px(-250.0),
+                            left: Px(0.0),
+                            width: Px(500.0),
+                            height: Px(500.0),
+                            ..default()
+                        },
+                    ),]
+                ),
+                (
+                    ArmorBar,
                     Node {
-                        position_type: PositionType::Absolute,
-                        top: Px(-250.0),
-                        left: Px(0.0),
-                        width: Px(500.0),
-                        height: Px(500.0),
+                        width: Percent(0.0),
+                        height: Percent(100.0),
                         ..default()
                     },
-                ),]
+                    BorderRadius::all(Px(10.0)),
+                    BackgroundColor(Color::from(tailwind::STONE_400.with_alpha(0.8))),
+                ),
+                (
+                    ShieldBar,
+                    Node {
+                        width: Percent(0.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    BorderRadius::all(Px(10.0)),
+                    BackgroundColor(Color::from(tailwind::SKY_400.with_alpha(0.8))),
+                ),
+            ],
+        )],
+    ));
+}
+
+fn spawn_stamina_bar(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Stamina HUD"),
+        StateScoped(Screen::Gameplay),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Percent(100.0),
+            height: Percent(100.0),
+            align_items: AlignItems::End,
+            justify_content: JustifyContent::Center,
+            bottom: Px(8.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Node {
+                width: Percent(100.0),
+                max_width: Px(300.0),
+                height: Px(5.0),
+                ..default()
+            },
+            BorderRadius::MAX,
+            BackgroundColor(Color::from(tailwind::ZINC_900.with_alpha(0.8))),
+            children![(
+                StaminaBar,
+                Node {
+                    width: Percent(100.0),
+                    height: Percent(100.0),
+                    ..default()
+                },
+                BorderRadius::MAX,
+                BackgroundColor(Color::from(tailwind::AMBER_400.with_alpha(0.8))),
             )],
         )],
     ));
 }
 
+fn update_stamina_bar(
+    player: Single<(&Stamina, &MovementStats), With<Player>>,
+    mut stamina_bar: Single<&mut Node, With<StaminaBar>>,
+) {
+    let (stamina, movement_stats) = player.into_inner();
+    stamina_bar.width = Percent(stamina.current / movement_stats.max_stamina * 100.0);
+}
+
+/// The health bar is split into segments for health, armor and shield,
+/// each taking up space proportional to its maximum value.
 fn update_health_bar(
-    health: Single<Option<&Health>, With<Player>>,
-    mut health_bar: Single<&mut Node, With<HealthBar>>,
+    player: Single<(Option<&Health>, Option<&Armor>, Option<&Shield>), With<Player>>,
+    mut health_bar: Single<&mut Node, (With<HealthBar>, Without<ArmorBar>, Without<ShieldBar>)>,
+    mut armor_bar: Single<&mut Node, (With<ArmorBar>, Without<ShieldBar>)>,
+    mut shield_bar: Single<&mut Node, With<ShieldBar>>,
+) {
+    let (health, armor, shield) = player.into_inner();
+    let Some(health) = health else {
+        health_bar.width = Percent(0.0);
+        armor_bar.width = Percent(0.0);
+        shield_bar.width = Percent(0.0);
+        return;
+    };
+    let armor = armor.map_or((0.0, 0.0), |a| (a.current, a.max));
+    let shield = shield.map_or((0.0, 0.0), |s| (s.current, s.max));
+    let total = health.max + armor.1 + shield.1;
+    health_bar.width = Percent(health.current / total * 100.0);
+    armor_bar.width = Percent(armor.0 / total * 100.0);
+    shield_bar.width = Percent(shield.0 / total * 100.0);
+}
+
+fn spawn_ammo_text(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands.spawn((
+        Name::new("Ammo HUD"),
+        StateScoped(Screen::Gameplay),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Px(30.0),
+            bottom: Px(20.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Text::new(""),
+            TextFont::from_font_size(26.0).with_font(fonts.default.clone()),
+            AmmoText,
+        )],
+    ));
+}
+
+fn update_ammo_text(
+    ammo: Single<&Ammo, With<Player>>,
+    mut ammo_text: Single<(&mut Text, &mut TextColor), With<AmmoText>>,
 ) {
-    let hp = health.map(|h| h.fraction()).unwrap_or(0.0);
-    health_bar.width = Percent(hp * 100.0);
+    let (text, color) = &mut *ammo_text;
+    let label = format!("Shells: {}/{}", ammo.current, ammo.max);
+    if text.0 != label {
+        text.0 = label;
+    }
+    let empty_color = if ammo.current == 0 {
+        Color::from(tailwind::RED_500)
+    } else {
+        Color::WHITE
+    };
+    color.set_if_neq(TextColor(empty_color));
+}
+
+fn spawn_status_effect_hud(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands.spawn((
+        Name::new("Status Effect HUD"),
+        StateScoped(Screen::Gameplay),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Percent(100.0),
+            height: Percent(100.0),
+            align_items: AlignItems::End,
+            justify_content: JustifyContent::Center,
+            bottom: Px(45.0),
+            column_gap: Px(20.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        StatusEffectHud,
+        // Keep the fonts around for the children spawned in `update_status_effect_hud`.
+        TextFont::from_font_size(18.0).with_font(fonts.default.clone()),
+    ));
+}
+
+fn update_status_effect_hud(
+    status_effects: Single<Option<&StatusEffects>, With<Player>>,
+    hud: Single<(Entity, &TextFont, Option<&Children>), With<StatusEffectHud>>,
+    texts: Query<&Text>,
+    mut commands: Commands,
+) {
+    let labels = status_effects
+        .into_inner()
+        .map(|status_effects| {
+            status_effects
+                .iter()
+                .map(|effect| (effect.kind, status_effect_label(effect)))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let (hud, font, children) = hud.into_inner();
+    // The countdowns only change once per second, so only rebuild the row when a label changed.
+    let shown = children
+        .into_iter()
+        .flatten()
+        .filter_map(|child| texts.get(*child).ok());
+    if shown
+        .map(|text| text.as_str())
+        .eq(labels.iter().map(|(_, label)| label.as_str()))
+    {
+        return;
+    }
+    commands.entity(hud).despawn_related::<Children>();
+    for (kind, label) in labels {
+        let color = match kind {
+            StatusEffectKind::Burning => tailwind::ORANGE_500,
+            StatusEffectKind::Slowed => tailwind::SKY_400,
+            StatusEffectKind::Stunned => tailwind::YELLOW_300,
+            StatusEffectKind::Bleeding => tailwind::RED_600,
+        };
+        commands.entity(hud).with_child((
+            Text::new(label),
+            font.clone(),
+            TextColor(Color::from(color)),
+        ));
+    }
+}
+
+fn status_effect_label(effect: &StatusEffect) -> String {
+    let remaining_secs = effect.remaining.remaining_secs().ceil();
+    if effect.stacks > 1 {
+        format!(
+            "{} x{} ({remaining_secs}s)",
+            effect.kind.label(),
+            effect.stacks,
+        )
+    } else {
+        format!("{} ({remaining_secs}s)", effect.kind.label())
+    }
 }
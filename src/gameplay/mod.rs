@@ -7,13 +7,18 @@ use bevy::prelude::*;
 
 mod animation;
 pub(crate) mod crosshair;
+pub(crate) mod damage_feedback;
 pub(crate) mod explosion;
 pub(crate) mod gore_settings;
 pub(crate) mod health;
 pub(crate) mod hud;
 pub(crate) mod level;
 pub(crate) mod npc;
+pub(crate) mod pickups;
 pub(crate) mod player;
+pub(crate) mod respawn;
+pub(crate) mod status_effects;
+pub(crate) mod surface;
 pub(crate) mod time;
 pub(crate) mod upgrades;
 pub(crate) mod waves;
@@ -22,15 +27,22 @@ pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         animation::plugin,
         crosshair::plugin,
+        damage_feedback::plugin,
         explosion::plugin,
         gore_settings::plugin,
         npc::plugin,
+        pickups::plugin,
         player::plugin,
         health::plugin,
+        status_effects::plugin,
         hud::plugin,
         waves::plugin,
         time::plugin,
         upgrades::plugin,
+    ));
+    app.add_plugins((
+        surface::plugin,
+        respawn::plugin,
         // This plugin preloads the level,
         // so make sure to add it last.
         level::plugin,
@@ -1,5 +1,6 @@
 use bevy::{
     audio::{SpatialScale, Volume},
+    diagnostic::FrameCount,
     prelude::*,
 };
 use bevy_landmass::AgentState;
@@ -9,14 +10,23 @@ use rand::Rng as _;
 
 use crate::{
     PostPhysicsAppSystems,
-    audio::SoundEffect,
+    audio::{SoundEffect, SpatialVoice, VoicePriority},
     gameplay::{
         npc::{assets::NpcAssets, stats::NpcStats},
         player::Player,
+        status_effects::StatusEffects,
     },
 };
 
-use super::{attack::Attacking, navigation::Agent};
+use super::{
+    attack::{AttackSet, Attacking},
+    attack_slots::AttackSlot,
+    kamikaze::Kamikaze,
+    lod::NpcLod,
+    nav_link::NavLinkRoute,
+    navigation::Agent,
+    ranged::Ranged,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<AiState>();
@@ -34,40 +44,88 @@ pub(crate) enum AiState {
     Chase,
     Stagger(Timer),
     Attack,
+    /// A ranged NPC moving to a spot from which it can shoot at the player.
+    Reposition(Vec3),
+    /// A ranged NPC aiming at the player. It fires when the timer finishes.
+    Aim {
+        timer: Timer,
+        dir: Option<Dir3>,
+    },
+    /// An NPC with perception that hasn't noticed the player, walking to a random spot.
+    Wander(Vec3),
+    /// An NPC with perception checking out where it last saw or heard the player.
+    Investigate(Vec3),
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
-fn update_ai_state(
-    mut ai_state: Query<(
-        Entity,
-        &mut AiState,
-        &NpcStats,
-        &Agent,
-        &Transform,
-        Has<Attacking>,
-    )>,
+pub(super) fn update_ai_state(
+    mut ai_state: Query<
+        (
+            Entity,
+            &mut AiState,
+            &NpcStats,
+            &Agent,
+            &Transform,
+            &AttackSet,
+            Has<Attacking>,
+            Has<Kamikaze>,
+            Option<&AttackSlot>,
+            Option<&StatusEffects>,
+            Has<NavLinkRoute>,
+            Option<&NpcLod>,
+        ),
+        Without<Ranged>,
+    >,
     player: Single<&Transform, With<Player>>,
     agent_state: Query<&AgentState>,
     mut npc_assets: ResMut<NpcAssets>,
+    frame: Res<FrameCount>,
     mut commands: Commands,
 ) {
-    for (entity, mut ai_state, stats, agent, transform, attacking) in &mut ai_state {
+    for (
+        entity,
+        mut ai_state,
+        stats,
+        agent,
+        transform,
+        attack_set,
+        attacking,
+        kamikaze,
+        attack_slot,
+        status_effects,
+        using_nav_link,
+        lod,
+    ) in &mut ai_state
+    {
+        if lod.is_some_and(|lod| !lod.should_update(&frame)) {
+            continue;
+        }
         let Ok(agent_state) = agent_state.get(**agent) else {
             continue;
         };
         match ai_state.clone() {
             AiState::Chase => {
-                if matches!(agent_state, AgentState::ReachedTarget) {
+                let stunned = status_effects.is_some_and(StatusEffects::is_stunned);
+                // Kamikazes don't attack, they keep running into the player until they explode.
+                // NPCs waiting for an attack slot have reached their waiting spot, not the player.
+                // Neither have NPCs heading for an off-mesh link.
+                let may_attack =
+                    !kamikaze && !using_nav_link && attack_slot.is_none_or(|slot| slot.may_attack);
+                if !stunned && may_attack && matches!(agent_state, AgentState::ReachedTarget) {
                     *ai_state = AiState::Attack;
                     let target = Vec3::new(
                         player.translation.x,
                         transform.translation.y,
                         player.translation.z,
                     );
+                    let rng = &mut rand::thread_rng();
+                    let attack = attack_set.pick(rng);
                     commands.entity(entity).insert(Attacking {
                         dir: Dir3::try_from(target - transform.translation).ok(),
-                        speed: rand::thread_rng().gen_range(stats.attack_speed_range.clone()),
-                        damage: stats.attack_damage,
+                        speed: rng.gen_range(stats.attack_speed_range.clone())
+                            * attack.speed_multiplier,
+                        damage: stats.attack_damage * attack.damage_multiplier,
+                        attack,
                     });
                     let handle = npc_assets
                         .attack_sound
@@ -83,6 +141,7 @@ fn update_ai_state(
                             .with_speed(1.0 / stats.size * speed_mod)
                             .with_spatial_scale(SpatialScale::new(1.0 / 7.5)),
                         SoundEffect,
+                        SpatialVoice(VoicePriority::Normal),
                     ));
                 }
             }
@@ -96,6 +155,12 @@ fn update_ai_state(
                     *ai_state = AiState::Chase;
                 }
             }
+            // Only ranged NPCs use these, see `ranged::update_ranged_ai_state`.
+            AiState::Reposition(..) | AiState::Aim { .. } => {
+                *ai_state = AiState::Chase;
+            }
+            // Handled by `perception::update_perception`.
+            AiState::Wander(..) | AiState::Investigate(..) => {}
         }
     }
 }
@@ -2,7 +2,7 @@
 
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{diagnostic::FrameCount, prelude::*};
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
 use bevy_tnua::{TnuaAnimatingState, TnuaAnimatingStateDirective, prelude::*};
@@ -10,7 +10,14 @@ use rand::Rng;
 
 use crate::{PostPhysicsAppSystems, gameplay::animation::AnimationPlayers};
 
-use super::{assets::NpcAssets, attack::Attacking};
+use super::{
+    ai_state::AiState,
+    assets::NpcAssets,
+    attack::Attacking,
+    dismemberment::Crawler,
+    lod::{LodTier, NpcLod},
+    nav_link::{NavLinkKind, NavLinkRoute},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<NpcAnimations>();
@@ -68,7 +75,15 @@ pub(crate) enum NpcAnimationState {
     Standing,
     Airborne,
     Attack(f32),
+    /// A ranged NPC aiming. Plays the attack animation so that it ends as the projectile is fired.
+    Aim(f32),
     Walking(f32),
+    /// A [`Crawler`] dragging itself along. Plays the walk animation slowly on the tipped-over model.
+    Crawling(f32),
+    /// Jumping across an off-mesh link. We have no jump animation, so this plays the attack animation as a leap.
+    Leaping,
+    /// Climbing up an off-mesh link. Plays the walk animation quickly, which looks like scrambling up the ledge.
+    Climbing,
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -79,15 +94,48 @@ fn play_animations(
         &TnuaController,
         &AnimationPlayers,
         Option<&Attacking>,
+        &AiState,
+        Has<Crawler>,
+        Option<&NavLinkRoute>,
+        Option<&NpcLod>,
     )>,
     mut q_animation: Query<(
         &NpcAnimations,
         &mut AnimationPlayer,
         &mut AnimationTransitions,
     )>,
+    frame: Res<FrameCount>,
     mut commands: Commands,
 ) {
-    for (entity, mut animating_state, controller, anim_players, attacking) in &mut query {
+    for (
+        entity,
+        mut animating_state,
+        controller,
+        anim_players,
+        attacking,
+        ai_state,
+        crawling,
+        route,
+        lod,
+    ) in &mut query
+    {
+        match lod.map(|lod| lod.tier) {
+            // Nobody sees these NPCs, so we stop sampling their animations altogether
+            // and start over from scratch once they get a higher tier again.
+            Some(LodTier::Minimal) => {
+                let mut iter = q_animation.iter_many_mut(anim_players.iter());
+                while let Some((_, mut anim_player, _)) = iter.fetch_next() {
+                    anim_player.stop_all();
+                }
+                *animating_state = TnuaAnimatingState::default();
+                continue;
+            }
+            _ if lod.is_some_and(|lod| !lod.should_update(&frame)) => continue,
+            _ => {}
+        }
+        let link_kind = route
+            .filter(|route| route.is_traversing())
+            .map(|route| route.kind);
         let mut iter = q_animation.iter_many_mut(anim_players.iter());
         while let Some((animations, mut anim_player, mut transitions)) = iter.fetch_next() {
             match animating_state.update_by_discriminant({
@@ -100,6 +148,13 @@ fn play_animations(
                     } else {
                         NpcAnimationState::Attack(attacking.speed)
                     }
+                } else if link_kind == Some(NavLinkKind::Jump) {
+                    NpcAnimationState::Leaping
+                } else if link_kind == Some(NavLinkKind::Climb) {
+                    NpcAnimationState::Climbing
+                } else if let AiState::Aim { timer, .. } = ai_state {
+                    // The attack animation reaches its hit after one second at normal speed.
+                    NpcAnimationState::Aim(1.0 / timer.duration().as_secs_f32().max(0.1))
                 } else {
                     let Some((_, basis_state)) = controller.concrete_basis::<TnuaBuiltinWalk>()
                     else {
@@ -108,6 +163,8 @@ fn play_animations(
                     let speed = basis_state.running_velocity.length();
                     if controller.is_airborne().unwrap() {
                         NpcAnimationState::Airborne
+                    } else if speed > 0.01 && crawling {
+                        NpcAnimationState::Crawling(speed)
                     } else if speed > 0.01 {
                         NpcAnimationState::Walking(speed)
                     } else {
@@ -123,6 +180,13 @@ fn play_animations(
                             let anim_speed = (speed / 5.0).max(0.2);
                             playing_animation.set_speed(anim_speed);
                         }
+                    } else if let NpcAnimationState::Crawling(speed) = state {
+                        if let Some((_index, playing_animation)) =
+                            anim_player.playing_animations_mut().next()
+                        {
+                            let anim_speed = (speed / 10.0).max(0.1);
+                            playing_animation.set_speed(anim_speed);
+                        }
                     }
                 }
                 TnuaAnimatingStateDirective::Alter {
@@ -159,7 +223,35 @@ fn play_animations(
                             )
                             .set_speed(*speed);
                     }
-                    NpcAnimationState::Walking(_speed) => {
+                    NpcAnimationState::Aim(speed) => {
+                        transitions
+                            .play(
+                                &mut anim_player,
+                                animations.attack,
+                                Duration::from_millis(100),
+                            )
+                            .set_speed(*speed);
+                    }
+                    NpcAnimationState::Leaping => {
+                        transitions
+                            .play(
+                                &mut anim_player,
+                                animations.attack,
+                                Duration::from_millis(100),
+                            )
+                            .set_speed(1.5);
+                    }
+                    NpcAnimationState::Climbing => {
+                        transitions
+                            .play(
+                                &mut anim_player,
+                                animations.walk,
+                                Duration::from_millis(200),
+                            )
+                            .set_speed(2.5)
+                            .repeat();
+                    }
+                    NpcAnimationState::Walking(_speed) | NpcAnimationState::Crawling(_speed) => {
                         transitions
                             .play(
                                 &mut anim_player,
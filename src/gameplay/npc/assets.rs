@@ -18,7 +18,7 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(Resource)]
 pub(crate) struct NpcAssets {
     #[dependency]
-    pub(crate) _model: Handle<Scene>,
+    pub(crate) model: Handle<Scene>,
     #[dependency]
     pub(crate) gib_head: Handle<Scene>,
     #[dependency]
@@ -49,6 +49,10 @@ pub(crate) struct NpcAssets {
     pub(crate) stagger_sound: ShuffleBag<Handle<AudioSource>>,
     #[dependency]
     pub(crate) attack_sound: ShuffleBag<Handle<AudioSource>>,
+    #[dependency]
+    pub(crate) spit_sound: ShuffleBag<Handle<AudioSource>>,
+    #[dependency]
+    pub(crate) warning_sound: ShuffleBag<Handle<AudioSource>>,
 }
 
 impl FromWorld for NpcAssets {
@@ -63,7 +67,7 @@ impl FromWorld for NpcAssets {
             gib_leg: assets.load("models/zombie_3/gib_leg.gltf#Scene0"),
             gib_foot: assets.load("models/zombie_3/gib_foot.gltf#Scene0"),
             gib_pelvis: assets.load("models/zombie_3/gib_pelvis.gltf#Scene0"),
-            _model: assets.load(Npc::scene_path()),
+            model: assets.load(Npc::scene_path()),
             attack_animation: assets.load(Npc::animation_path(0)),
             idle_animation: assets.load(Npc::animation_path(1)),
             walk_animation: assets.load(Npc::animation_path(2)),
@@ -126,6 +130,22 @@ impl FromWorld for NpcAssets {
                 rng,
             )
             .unwrap(),
+            spit_sound: ShuffleBag::try_new(
+                [
+                    assets.load("audio/sound_effects/zombie/04-shriek.ogg"),
+                    assets.load("audio/sound_effects/zombie/08-roar.ogg"),
+                ],
+                rng,
+            )
+            .unwrap(),
+            warning_sound: ShuffleBag::try_new(
+                [
+                    assets.load("audio/sound_effects/zombie/03-lunging-snarl.ogg"),
+                    assets.load("audio/sound_effects/zombie/04-shriek.ogg"),
+                ],
+                rng,
+            )
+            .unwrap(),
         }
     }
 }
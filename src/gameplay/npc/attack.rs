@@ -2,15 +2,21 @@ use avian3d::prelude::*;
 use bevy::{prelude::*, time::Stopwatch};
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
+use bevy_tnua::prelude::*;
+use rand::seq::SliceRandom as _;
 
 use crate::{
     PrePhysicsAppSystems,
-    gameplay::{health::OnDamage, npc::stats::NpcStats, player::Player},
+    gameplay::{
+        health::{DamageKind, OnDamage},
+        npc::stats::NpcStats,
+        player::{Player, movement::OnKnockback},
+    },
     third_party::avian3d::CollisionLayer,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<Attacking>();
+    app.register_type::<(Attacking, AttackSet, AttackDefinition, AttackKind)>();
     app.register_type::<AttackPhase>();
     app.register_type::<AttackStopwatch>();
     app.add_systems(
@@ -45,27 +51,42 @@ fn update_attack_phase(
         &Attacking,
         &mut AttackStopwatch,
         &NpcStats,
+        &mut TnuaController,
     )>,
     time: Res<Time>,
     mut commands: Commands,
 ) {
-    for (entity, mut phase, attacking, mut stopwatch, stats) in query.iter_mut() {
+    for (entity, mut phase, attacking, mut stopwatch, stats, mut controller) in query.iter_mut() {
         stopwatch.0.tick(time.delta());
+        let attack = &attacking.attack;
         match *phase {
             AttackPhase::Windup => {
                 if stopwatch.0.elapsed_secs() > attacking.attack_start_secs() {
                     *phase = AttackPhase::Hit;
+                    let size = stats.size;
                     commands
                         .spawn((
                             HitboxOf(entity),
                             ChildOf(entity),
                             Sensor,
-                            Transform::from_xyz(0.0, 0.0, -1.5),
-                            Collider::cuboid(1.5 * stats.size, 1.5 * stats.size, 1.5 * stats.size),
+                            Transform::from_translation(attack.hitbox_offset),
+                            Collider::cuboid(
+                                attack.hitbox_size.x * size,
+                                attack.hitbox_size.y * size,
+                                attack.hitbox_size.z * size,
+                            ),
                             CollisionEventsEnabled,
                             CollisionLayers::new(CollisionLayer::Sensor, CollisionLayer::Player),
                         ))
                         .observe(hit_player);
+                    // Like knockback on the player, the lunge goes through Tnua
+                    // so that it doesn't fight the float spring and the walk basis.
+                    if let Some(dir) = attacking.dir {
+                        controller.action(TnuaBuiltinKnockback {
+                            shove: dir * attack.lunge_speed,
+                            ..default()
+                        });
+                    }
                 }
             }
             AttackPhase::Hit => {
@@ -87,7 +108,9 @@ fn update_attack_phase(
 #[cfg_attr(feature = "hot_patch", hot)]
 fn hit_player(
     trigger: Trigger<OnCollisionStart>,
-    player: Query<(), With<Player>>,
+    player: Query<&GlobalTransform, With<Player>>,
+    hitboxes: Query<(&HitboxOf, &GlobalTransform)>,
+    attackers: Query<&Attacking>,
     name: Query<NameOrEntity>,
     mut commands: Commands,
 ) {
@@ -95,12 +118,34 @@ fn hit_player(
         error!("Enemy hit collision without body");
         return;
     };
-    if !player.contains(body) {
+    let Ok(player_transform) = player.get(body) else {
         let name = name.get(body).unwrap();
         error!("Enemy hit non-player: {name}");
         return;
-    }
-    commands.entity(body).trigger(OnDamage(10.0));
+    };
+    let Ok((hitbox_of, hitbox_transform)) = hitboxes.get(trigger.target()) else {
+        return;
+    };
+    let Ok(attacking) = attackers.get(hitbox_of.0) else {
+        return;
+    };
+    let hitbox_position = hitbox_transform.translation();
+    let direction = Dir3::new(player_transform.translation() - hitbox_position).ok();
+    commands.entity(body).trigger(
+        OnDamage::new(attacking.damage, DamageKind::Melee)
+            .with_instigator(hitbox_of.0)
+            .at(hitbox_position)
+            .in_direction(direction),
+    );
+
+    // Knock the player away from the hitbox, and a bit upwards so that they don't just slide on the ground.
+    let away = direction.map_or(Vec3::ZERO, |direction| {
+        direction.with_y(0.0).normalize_or_zero()
+    });
+    // Going through Tnua keeps the knockback from fighting the float spring.
+    commands.entity(body).trigger(OnKnockback {
+        shove: (away + Vec3::Y * 0.3) * attacking.attack.knockback,
+    });
 }
 
 #[derive(Component, Deref, DerefMut, Debug, Reflect)]
@@ -119,6 +164,98 @@ pub(crate) struct Attacking {
     pub(crate) speed: f32,
     pub(crate) damage: f32,
     pub(crate) dir: Option<Dir3>,
+    pub(crate) attack: AttackDefinition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub(crate) enum AttackKind {
+    /// A quick swipe right in front of the NPC.
+    Swipe,
+    /// A leap forward that covers some distance before hitting.
+    Lunge,
+    /// A slow, heavy blow that hits everything around the NPC.
+    Slam,
+}
+
+/// How a single melee attack plays out.
+/// Hitbox sizes are given for an NPC of size 1 and scaled by [`NpcStats::size`].
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub(crate) struct AttackDefinition {
+    pub(crate) kind: AttackKind,
+    /// The animation frame at which the hitbox appears.
+    pub(crate) hit_start_frame: u32,
+    /// The animation frame at which the hitbox disappears.
+    pub(crate) hit_end_frame: u32,
+    /// Multiplies the attack animation speed rolled from [`NpcStats::attack_speed_range`].
+    pub(crate) speed_multiplier: f32,
+    /// The full extents of the hitbox.
+    pub(crate) hitbox_size: Vec3,
+    /// The position of the hitbox relative to the NPC.
+    pub(crate) hitbox_offset: Vec3,
+    /// Multiplies [`NpcStats::attack_damage`].
+    pub(crate) damage_multiplier: f32,
+    /// The velocity the player gets knocked back with.
+    pub(crate) knockback: f32,
+    /// The velocity the NPC jumps forward with when the hitbox appears.
+    pub(crate) lunge_speed: f32,
+}
+
+impl AttackDefinition {
+    pub(crate) const SWIPE: Self = Self {
+        kind: AttackKind::Swipe,
+        hit_start_frame: 24,
+        hit_end_frame: 29,
+        speed_multiplier: 1.0,
+        hitbox_size: Vec3::splat(1.5),
+        hitbox_offset: Vec3::new(0.0, 0.0, -1.5),
+        damage_multiplier: 1.0,
+        knockback: 3.0,
+        lunge_speed: 0.0,
+    };
+
+    pub(crate) const LUNGE: Self = Self {
+        kind: AttackKind::Lunge,
+        hit_start_frame: 22,
+        hit_end_frame: 30,
+        speed_multiplier: 1.2,
+        hitbox_size: Vec3::new(1.2, 1.5, 2.0),
+        hitbox_offset: Vec3::new(0.0, 0.0, -1.8),
+        damage_multiplier: 0.8,
+        knockback: 2.0,
+        lunge_speed: 9.0,
+    };
+
+    pub(crate) const SLAM: Self = Self {
+        kind: AttackKind::Slam,
+        hit_start_frame: 26,
+        hit_end_frame: 29,
+        speed_multiplier: 0.75,
+        hitbox_size: Vec3::new(3.5, 1.5, 3.5),
+        hitbox_offset: Vec3::new(0.0, -0.3, -0.8),
+        damage_multiplier: 1.5,
+        knockback: 9.0,
+        lunge_speed: 0.0,
+    };
+}
+
+/// The attacks an NPC picks from when it reaches the player.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct AttackSet(pub(crate) Vec<AttackDefinition>);
+
+impl Default for AttackSet {
+    fn default() -> Self {
+        Self(vec![AttackDefinition::SWIPE])
+    }
+}
+
+impl AttackSet {
+    pub(crate) fn pick(&self, rng: &mut impl rand::Rng) -> AttackDefinition {
+        self.0
+            .choose(rng)
+            .copied()
+            .unwrap_or(AttackDefinition::SWIPE)
+    }
 }
 
 #[derive(Component, Debug, Reflect, Default, Clone)]
@@ -136,11 +273,11 @@ enum AttackPhase {
 
 impl Attacking {
     pub(crate) fn attack_start_secs(&self) -> f32 {
-        self.frame_to_secs(24)
+        self.frame_to_secs(self.attack.hit_start_frame)
     }
 
     pub(crate) fn attack_end_secs(&self) -> f32 {
-        self.frame_to_secs(29)
+        self.frame_to_secs(self.attack.hit_end_frame)
     }
 
     fn frame_to_secs(&self, frame: u32) -> f32 {
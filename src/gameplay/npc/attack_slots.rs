@@ -0,0 +1,167 @@
+//! Crowd tactics for melee NPCs. Instead of everyone pathing to the player and piling up,
+//! only a few of the closest NPCs get a slot on a ring around the player and are allowed to attack.
+//! The rest circle the player further out, waiting for a slot to free up.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_landmass::Archipelago3d;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    PrePhysicsAppSystems,
+    gameplay::{
+        npc::{
+            Npc,
+            ai_state::AiState,
+            kamikaze::Kamikaze,
+            navigation::{sample_navmesh, update_agent_target},
+            ranged::Ranged,
+            stats::NpcStats,
+        },
+        player::navmesh_position::LastValidPlayerNavmeshPosition,
+    },
+    third_party::bevy_landmass::BaseArchipelago,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(AttackSlots, AttackSlot)>();
+    app.init_resource::<AttackSlots>();
+    app.add_systems(
+        RunFixedMainLoop,
+        assign_attack_slots
+            .in_set(PrePhysicsAppSystems::UpdateNavmeshTargets)
+            .before(update_agent_target),
+    );
+}
+
+/// Settings for how NPCs share the space around the player.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct AttackSlots {
+    /// How many NPCs may attack the player at the same time.
+    pub(crate) max_attackers: usize,
+    /// The distance from the player at which waiting NPCs circle.
+    pub(crate) waiting_radius: f32,
+    /// How far ahead on their circle waiting NPCs aim, in radians.
+    /// Larger values make them circle the player faster.
+    pub(crate) orbit_lead: f32,
+}
+
+impl Default for AttackSlots {
+    fn default() -> Self {
+        Self {
+            max_attackers: 4,
+            waiting_radius: 6.0,
+            orbit_lead: 0.6,
+        }
+    }
+}
+
+/// Where a chasing NPC should go instead of straight to the player.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub(crate) struct AttackSlot {
+    pub(crate) position: Vec3,
+    /// Whether the NPC may attack once it reaches its slot.
+    /// NPCs that may not attack are waiting for a slot to free up.
+    pub(crate) may_attack: bool,
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn assign_attack_slots(
+    mut npcs: Query<
+        (
+            Entity,
+            &Transform,
+            &NpcStats,
+            &AiState,
+            Option<&mut AttackSlot>,
+        ),
+        (With<Npc>, Without<Ranged>, Without<Kamikaze>),
+    >,
+    player_position: Single<&LastValidPlayerNavmeshPosition>,
+    archipelago: Single<&Archipelago3d, With<BaseArchipelago>>,
+    settings: Res<AttackSlots>,
+    mut commands: Commands,
+) {
+    let Some(player_position) = player_position.0 else {
+        return;
+    };
+
+    // NPCs that are already attacking keep their slot, the rest are ranked by how close they are.
+    let mut candidates = npcs
+        .iter()
+        .filter(|(.., ai_state, _)| matches!(ai_state, AiState::Chase | AiState::Attack))
+        .map(|(entity, transform, _, ai_state, _)| {
+            let offset = (transform.translation - player_position).xz();
+            (entity, matches!(ai_state, AiState::Attack), offset)
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|(_, a_attacking, a_offset), (_, b_attacking, b_offset)| {
+        b_attacking.cmp(a_attacking).then(
+            a_offset
+                .length_squared()
+                .total_cmp(&b_offset.length_squared()),
+        )
+    });
+
+    let slot_count = settings.max_attackers.max(1);
+    let mut free_slots = (0..slot_count).collect::<Vec<_>>();
+    for (rank, (entity, _, offset)) in candidates.into_iter().enumerate() {
+        let Ok((_, _, stats, _, attack_slot)) = npcs.get_mut(entity) else {
+            continue;
+        };
+        let bearing = offset.y.atan2(offset.x);
+        let (angle, radius, may_attack) = if rank < slot_count {
+            // Take the free slot closest to where the NPC already is around the player.
+            let (index, slot) = free_slots
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    let distance = |slot: usize| {
+                        let angle = slot as f32 / slot_count as f32 * TAU;
+                        angle_difference(angle, bearing).abs()
+                    };
+                    distance(**a).total_cmp(&distance(**b))
+                })
+                .map(|(index, slot)| (index, *slot))
+                .unwrap();
+            free_slots.swap_remove(index);
+            let angle = slot as f32 / slot_count as f32 * TAU;
+            (angle, stats.size * 1.2, true)
+        } else {
+            // Alternate the direction NPCs circle in so that they don't all bunch up on one side.
+            let direction = if entity.index() % 2 == 0 { 1.0 } else { -1.0 };
+            // Aim a bit ahead on the circle so that the NPC keeps moving.
+            (
+                bearing + settings.orbit_lead * direction,
+                settings.waiting_radius + stats.radius(),
+                false,
+            )
+        };
+
+        let ideal = player_position + Vec3::new(angle.cos(), 0.0, angle.sin()) * radius;
+        let position = sample_navmesh(&archipelago, ideal).unwrap_or(player_position);
+        let slot = AttackSlot {
+            position,
+            may_attack,
+        };
+        if let Some(mut attack_slot) = attack_slot {
+            *attack_slot = slot;
+        } else {
+            commands.entity(entity).try_insert(slot);
+        }
+    }
+}
+
+/// The signed difference between two angles, wrapped to `[-PI, PI]`.
+fn angle_difference(a: f32, b: f32) -> f32 {
+    let difference = (a - b).rem_euclid(TAU);
+    if difference > TAU / 2.0 {
+        difference - TAU
+    } else {
+        difference
+    }
+}
@@ -0,0 +1,233 @@
+//! Hit zones and dismemberment. Shots to the head deal extra damage,
+//! and enough damage to a leg tears it off and leaves the NPC crawling after the player, bleeding.
+//!
+//! The zombie model has no per-limb colliders, so hit zones are derived from
+//! where on the NPC's capsule a hit landed.
+//!
+//! Limbs only come off when dismemberment is enabled in the [`GoreSettings`].
+//! There is no crawling animation yet, so crawlers are the walking model tipped over onto its belly.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::prelude::*;
+use bevy_mesh_decal::spray_decal;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use rand::Rng as _;
+
+use crate::{
+    audio::VoicePriority,
+    gameplay::{
+        explosion::assets::ExplosionAssets,
+        gore_settings::{Gore, GoreSettings},
+        health::OnDamage,
+        npc::{
+            NpcModel,
+            assets::NpcAssets,
+            lifecycle::{Vocal, VocalOf, enemy_sound_effect, spawn_gib},
+            stats::NpcStats,
+        },
+        status_effects::{OnStatusEffect, StatusEffectKind, StatusEffectSpec},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(HitZone, Limbs, Crawler)>();
+    app.add_observer(damage_limbs);
+    app.add_observer(start_crawling);
+}
+
+/// Damage multiplier for hits to the head.
+pub(crate) const HEADSHOT_MULTIPLIER: f32 = 2.0;
+/// Crawlers move this much slower than the NPC did on its feet.
+pub(crate) const CRAWL_SPEED_FACTOR: f32 = 0.35;
+/// The fraction of an NPC's maximum health that each leg can take before coming off.
+const LEG_HEALTH_FRACTION: f32 = 0.35;
+/// Hits above this fraction of the NPC's height are headshots.
+const HEAD_HEIGHT_FRACTION: f32 = 0.82;
+/// Hits below this fraction of the NPC's height hit the legs.
+const LEG_HEIGHT_FRACTION: f32 = 0.4;
+/// How long an NPC bleeds after losing a leg.
+const LIMB_LOSS_BLEEDING_SECS: f32 = 6.0;
+
+/// The part of an NPC that a hit landed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub(crate) enum HitZone {
+    Head,
+    Torso,
+    LeftLeg,
+    RightLeg,
+}
+
+impl HitZone {
+    pub(crate) fn damage_multiplier(self) -> f32 {
+        match self {
+            HitZone::Head => HEADSHOT_MULTIPLIER,
+            HitZone::Torso | HitZone::LeftLeg | HitZone::RightLeg => 1.0,
+        }
+    }
+}
+
+/// Determines which part of the NPC was hit at `point` in world space.
+/// Crawlers are too close to the ground for distinct zones, so every hit counts as a torso hit.
+pub(crate) fn hit_zone(
+    transform: &Transform,
+    stats: &NpcStats,
+    crawling: bool,
+    point: Vec3,
+) -> HitZone {
+    if crawling {
+        return HitZone::Torso;
+    }
+    let local = transform.rotation.inverse() * (point - transform.translation);
+    // 0 at the bottom of the capsule, 1 at the top.
+    let height = (local.y + stats.half_height()) / stats.height();
+    if height > HEAD_HEIGHT_FRACTION {
+        HitZone::Head
+    } else if height > LEG_HEIGHT_FRACTION {
+        HitZone::Torso
+    } else if local.x < 0.0 {
+        HitZone::LeftLeg
+    } else {
+        HitZone::RightLeg
+    }
+}
+
+/// How much damage each leg of an NPC can still take before it comes off.
+/// A leg at or below zero has been torn off.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Limbs {
+    pub(crate) left_leg: f32,
+    pub(crate) right_leg: f32,
+}
+
+impl Limbs {
+    pub(crate) fn new(stats: &NpcStats) -> Self {
+        let leg_health = stats.health * LEG_HEALTH_FRACTION;
+        Self {
+            left_leg: leg_health,
+            right_leg: leg_health,
+        }
+    }
+}
+
+/// An NPC that lost a leg and now drags itself along the ground.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Crawler;
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn damage_limbs(
+    trigger: Trigger<OnDamage>,
+    mut npcs: Query<(&Transform, &NpcStats, &mut Limbs, Has<Crawler>)>,
+    npc_assets: Res<NpcAssets>,
+    mut explosion_assets: ResMut<ExplosionAssets>,
+    gore_settings: Res<GoreSettings>,
+    mut commands: Commands,
+) {
+    // The "Dismemberment" gore setting controls gibs, and losing a leg is no different.
+    if gore_settings.gibs == Gore::None {
+        return;
+    }
+    let entity = trigger.target();
+    let damage = trigger.event();
+    let Some(point) = damage.point else {
+        return;
+    };
+    let Ok((transform, stats, mut limbs, crawling)) = npcs.get_mut(entity) else {
+        return;
+    };
+    let zone = hit_zone(transform, stats, crawling, point);
+    let leg = match zone {
+        HitZone::LeftLeg => &mut limbs.left_leg,
+        HitZone::RightLeg => &mut limbs.right_leg,
+        HitZone::Head | HitZone::Torso => return,
+    };
+    if *leg <= 0.0 {
+        return;
+    }
+    *leg -= damage.amount;
+    if *leg > 0.0 {
+        return;
+    }
+
+    let side = if zone == HitZone::LeftLeg { -1.0 } else { 1.0 };
+    let hip = transform.translation
+        + transform.rotation * Vec3::new(side * stats.radius() * 0.5, 0.0, 0.0);
+    let foot = hip - Vec3::Y * stats.half_height();
+    spawn_gib(
+        &mut commands,
+        &npc_assets.gib_leg,
+        hip.lerp(foot, 0.4),
+        stats,
+        &gore_settings,
+    );
+    spawn_gib(
+        &mut commands,
+        &npc_assets.gib_foot,
+        foot,
+        stats,
+        &gore_settings,
+    );
+
+    if gore_settings.blood_decals != Gore::None {
+        // A pool of blood on the ground where the leg came off.
+        let rng = &mut rand::thread_rng();
+        let blood = explosion_assets.blood_splatter.pick(rng).clone();
+        let size = stats.size * 1.2;
+        let rotation =
+            Quat::from_rotation_y(rng.gen_range(0.0..TAU)) * Quat::from_rotation_x(-FRAC_PI_2);
+        spray_decal(
+            &mut commands,
+            blood,
+            Transform::from_translation(foot)
+                .with_rotation(rotation)
+                .with_scale(Vec3::new(size, size, size)),
+        );
+    }
+    commands.entity(entity).try_insert(Crawler);
+    let bleeding = StatusEffectSpec {
+        stacks: 2,
+        ..StatusEffectSpec::new(StatusEffectKind::Bleeding, LIMB_LOSS_BLEEDING_SECS)
+    };
+    commands
+        .entity(entity)
+        .trigger(OnStatusEffect::new(bleeding).with_instigator(damage.instigator));
+}
+
+/// Drop the model to the ground and let out a scream.
+#[cfg_attr(feature = "hot_patch", hot)]
+fn start_crawling(
+    trigger: Trigger<OnAdd, Crawler>,
+    npcs: Query<(&Transform, &NpcStats, &Children)>,
+    mut models: Query<&mut Transform, (With<NpcModel>, Without<NpcStats>)>,
+    mut npc_assets: ResMut<NpcAssets>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    let Ok((transform, stats, children)) = npcs.get(entity) else {
+        return;
+    };
+    let mut models = models.iter_many_mut(children);
+    while let Some(mut model) = models.fetch_next() {
+        // We don't have a crawling animation, so we tip the walking model over onto its belly.
+        model.rotation = Quat::from_rotation_x(-FRAC_PI_2 * 0.85);
+        model.translation.z = stats.height() * 0.3;
+    }
+
+    commands.entity(entity).queue_handled(
+        |mut entity: EntityWorldMut| {
+            entity.despawn_related::<Vocal>();
+        },
+        bevy::ecs::error::ignore,
+    );
+    let handle = npc_assets
+        .stagger_sound
+        .pick(&mut rand::thread_rng())
+        .clone();
+    commands.spawn((
+        enemy_sound_effect(handle, *transform, stats, VoicePriority::Normal),
+        VocalOf(entity),
+    ));
+}
@@ -0,0 +1,167 @@
+//! Kamikaze NPCs. They sprint at the player and blow up on contact.
+//! Shooting one early sets off its explosion among the zombies around it instead.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    PostPhysicsAppSystems,
+    audio::VoicePriority,
+    gameplay::{
+        explosion::{ExplodeOnContact, Exploded, Explosive, OnExplode},
+        npc::{
+            assets::NpcAssets,
+            lifecycle::{Vocal, VocalOf, enemy_sound_effect},
+            stats::NpcStats,
+        },
+        player::Player,
+        status_effects::{StatusEffectKind, StatusEffectSpec},
+    },
+    screens::Screen,
+    third_party::avian3d::CollisionLayer,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Kamikaze, KamikazeGlow)>();
+    app.add_observer(spawn_glow);
+    app.add_systems(
+        Update,
+        (update_fuses, update_glows)
+            .in_set(PostPhysicsAppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The brightest the glow gets right before the explosion.
+const MAX_GLOW_INTENSITY: f32 = 300_000.0;
+
+/// Marks an NPC as exploding on contact with the player instead of attacking in melee.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+#[require(ExplodeOnContact = ExplodeOnContact {
+    layers: LayerMask::from(CollisionLayer::Player),
+})]
+pub(crate) struct Kamikaze {
+    /// The kamikaze starts glowing and screeching when the player is closer than this.
+    pub(crate) warning_distance: f32,
+    /// The kamikaze detonates by itself after staying this close to the player for the duration of the fuse.
+    /// This catches the cases where it stops right in front of the player without touching them.
+    pub(crate) fuse_distance: f32,
+    pub(crate) fuse: Timer,
+    warned: bool,
+}
+
+impl Default for Kamikaze {
+    fn default() -> Self {
+        Self {
+            warning_distance: 10.0,
+            fuse_distance: 2.0,
+            fuse: Timer::from_seconds(0.3, TimerMode::Once),
+            warned: false,
+        }
+    }
+}
+
+impl Kamikaze {
+    /// The explosive used by kamikazes instead of the regular NPC one.
+    /// It is strong enough to set off a chain reaction among nearby zombies,
+    /// and briefly stuns everything caught in the blast.
+    pub(crate) fn explosive(stats: &NpcStats) -> Explosive {
+        Explosive {
+            radius: stats.size * 5.0,
+            impulse_strength: 20.0,
+            damage: stats.size * 200.0,
+            damages_player: true,
+            status_effect: Some(StatusEffectSpec::new(StatusEffectKind::Stunned, 0.75)),
+        }
+    }
+}
+
+/// The light that makes a [`Kamikaze`] glow as it gets close to the player.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct KamikazeGlow;
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn spawn_glow(trigger: Trigger<OnAdd, Kamikaze>, mut commands: Commands) {
+    commands.entity(trigger.target()).with_child((
+        Name::new("Kamikaze Glow"),
+        KamikazeGlow,
+        PointLight {
+            color: Color::srgb(1.0, 0.45, 0.1),
+            intensity: 0.0,
+            range: 8.0,
+            shadows_enabled: false,
+            ..default()
+        },
+    ));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_fuses(
+    mut kamikazes: Query<
+        (Entity, &mut Kamikaze, &Transform, &NpcStats, Has<Vocal>),
+        Without<Exploded>,
+    >,
+    player: Single<&Transform, With<Player>>,
+    mut npc_assets: ResMut<NpcAssets>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut kamikaze, transform, stats, has_vocal) in &mut kamikazes {
+        let distance = transform.translation.distance(player.translation);
+
+        if !kamikaze.warned && distance < kamikaze.warning_distance {
+            kamikaze.warned = true;
+            // The warning is more important than whatever the kamikaze was grunting before.
+            if has_vocal {
+                commands.entity(entity).queue_handled(
+                    |mut entity: EntityWorldMut| {
+                        entity.despawn_related::<Vocal>();
+                    },
+                    bevy::ecs::error::ignore,
+                );
+            }
+            let handle = npc_assets
+                .warning_sound
+                .pick(&mut rand::thread_rng())
+                .clone();
+            commands.spawn((
+                enemy_sound_effect(handle, *transform, stats, VoicePriority::High),
+                VocalOf(entity),
+            ));
+        }
+
+        if distance < kamikaze.fuse_distance {
+            kamikaze.fuse.tick(time.delta());
+            if kamikaze.fuse.finished() {
+                commands.entity(entity).trigger(OnExplode::default());
+            }
+        } else {
+            kamikaze.fuse.reset();
+        }
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_glows(
+    mut glows: Query<(&ChildOf, &mut PointLight), With<KamikazeGlow>>,
+    kamikazes: Query<(&Kamikaze, &Transform)>,
+    player: Single<&Transform, With<Player>>,
+    time: Res<Time>,
+) {
+    for (child_of, mut light) in &mut glows {
+        let Ok((kamikaze, transform)) = kamikazes.get(child_of.parent()) else {
+            continue;
+        };
+        let distance = transform.translation.distance(player.translation);
+        // 0 at the warning distance, 1 when touching the player.
+        let closeness = (1.0 - distance / kamikaze.warning_distance).clamp(0.0, 1.0);
+        // Pulse faster the closer the kamikaze gets.
+        let pulse_frequency = 4.0 + 16.0 * closeness;
+        let pulse = (time.elapsed_secs() * pulse_frequency).sin() * 0.5 + 0.5;
+        light.intensity = MAX_GLOW_INTENSITY * closeness * (0.4 + 0.6 * pulse);
+    }
+}
@@ -11,13 +11,20 @@ use bevy_simple_subsecond_system::hot;
 use rand::Rng;
 
 use crate::{
-    audio::SoundEffect,
+    audio::{SoundEffect, SpatialVoice, VoicePriority},
     despawn_after::{Despawn, DespawnAfter},
     gameplay::{
         explosion::{ExplodeOnDeath, OnExplode},
-        gore_settings::{Gore, GoreSettings},
+        gore_settings::{DeathStyle, Gore, GoreSettings},
         health::{OnDamage, OnDeath},
-        npc::{ai_state::AiState, assets::NpcAssets, stats::NpcStats},
+        npc::{
+            ai_state::AiState,
+            assets::NpcAssets,
+            lod::{LodTier, NpcLod},
+            ragdoll::{Ragdoll, spawn_ragdoll},
+            stats::NpcStats,
+        },
+        surface::SurfaceMaterial,
     },
     screens::{Screen, loading::LoadingScreen},
     third_party::avian3d::CollisionLayer,
@@ -31,21 +38,37 @@ pub(super) fn plugin(app: &mut App) {
 
 #[derive(Component, Clone, Copy, Debug, Default, Reflect)]
 #[reflect(Component)]
+#[require(SurfaceMaterial = SurfaceMaterial::Flesh)]
 pub struct Gib;
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn on_enemy_death(
     trigger: Trigger<OnDeath>,
-    enemies: Query<(&Transform, &NpcStats, Has<ExplodeOnDeath>)>,
+    enemies: Query<(&Transform, &NpcStats, &LinearVelocity, Has<ExplodeOnDeath>)>,
+    children: Query<&Children>,
+    nodes: Query<(&Name, &Transform)>,
     npc_assets: Res<NpcAssets>,
     gore_settings: Res<GoreSettings>,
+    time: Res<Time>,
     mut commands: Commands,
 ) {
     let entity = trigger.target();
-    let Ok((transform, stats, explode_on_death)) = enemies.get(entity) else {
+    let Ok((transform, stats, velocity, explode_on_death)) = enemies.get(entity) else {
         return;
     };
-    if gore_settings.gibs != Gore::None {
+    let ragdolls_enabled =
+        gore_settings.death_style == DeathStyle::Ragdoll && gore_settings.ragdoll_count > 0;
+    if gore_settings.gibs != Gore::None && ragdolls_enabled {
+        let ragdoll = Ragdoll::new(
+            entity,
+            trigger.event(),
+            velocity.0,
+            time.elapsed(),
+            &children,
+            &nodes,
+        );
+        spawn_ragdoll(&mut commands, ragdoll, transform, stats, &npc_assets);
+    } else if gore_settings.gibs != Gore::None {
         let mut rng = rand::thread_rng();
         let mut gibs = ShuffleBag::try_new(
             [
@@ -70,26 +93,7 @@ fn on_enemy_death(
             let offset_radius = 0.5;
             let offset = Sphere::new(offset_radius).sample_interior(&mut rng);
             let position = transform.translation + offset;
-
-            let mut entity_commands = commands.spawn((
-                Gib,
-                SceneRoot(gib.clone()),
-                Transform::from_translation(position).with_scale(Vec3::splat(stats.size)),
-                RigidBody::Dynamic,
-                ColliderConstructorHierarchy::new(ColliderConstructor::ConvexHullFromMesh)
-                    .with_default_layers(CollisionLayers::new(
-                        CollisionLayer::Gib,
-                        [CollisionLayer::Default],
-                    )),
-                StateScoped(Screen::Gameplay),
-                StateScoped(LoadingScreen::Shaders),
-            ));
-
-            entity_commands.observe(remove_shadow_caster);
-
-            if let Gore::Despawn(duration) = gore_settings.gibs {
-                entity_commands.insert(DespawnAfter::new(duration));
-            }
+            spawn_gib(&mut commands, gib, position, stats, &gore_settings);
         }
     }
 
@@ -102,7 +106,38 @@ fn on_enemy_death(
     );
 
     if explode_on_death {
-        commands.entity(entity).trigger(OnExplode);
+        commands
+            .entity(entity)
+            .trigger(OnExplode::caused_by(trigger.event()));
+    }
+}
+
+/// Spawns a single gib. The caller is responsible for checking that gibs are enabled in the [`GoreSettings`].
+pub(crate) fn spawn_gib(
+    commands: &mut Commands,
+    scene: &Handle<Scene>,
+    position: Vec3,
+    stats: &NpcStats,
+    gore_settings: &GoreSettings,
+) {
+    let mut entity_commands = commands.spawn((
+        Gib,
+        SceneRoot(scene.clone()),
+        Transform::from_translation(position).with_scale(Vec3::splat(stats.size)),
+        RigidBody::Dynamic,
+        ColliderConstructorHierarchy::new(ColliderConstructor::ConvexHullFromMesh)
+            .with_default_layers(CollisionLayers::new(
+                CollisionLayer::Gib,
+                [CollisionLayer::Default],
+            )),
+        StateScoped(Screen::Gameplay),
+        StateScoped(LoadingScreen::Shaders),
+    ));
+
+    entity_commands.observe(remove_shadow_caster);
+
+    if let Gore::Despawn(duration) = gore_settings.gibs {
+        entity_commands.insert(DespawnAfter::new(duration));
     }
 }
 
@@ -132,14 +167,18 @@ pub(crate) struct Vocal(Entity);
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn grunt_passively(
-    mut enemy: Query<(&AiState, &NpcStats, &Transform, Entity), Without<Vocal>>,
+    mut enemy: Query<(&AiState, &NpcStats, &Transform, Entity, Option<&NpcLod>), Without<Vocal>>,
     mut commands: Commands,
     time: Res<Time>,
     mut npc_assets: ResMut<NpcAssets>,
 ) {
-    for (ai_state, stats, transform, entity) in enemy.iter_mut() {
+    for (ai_state, stats, transform, entity, lod) in enemy.iter_mut() {
         if !matches!(*ai_state, AiState::Chase) {
-            return;
+            continue;
+        }
+        // Nobody would hear far away grunts over the horde anyway.
+        if lod.is_some_and(|lod| lod.tier != LodTier::Full) {
+            continue;
         }
 
         let grunt_chance_per_second = 0.3;
@@ -150,7 +189,7 @@ fn grunt_passively(
 
         let handle = npc_assets.idle_sound.pick(&mut rand::thread_rng()).clone();
         commands.spawn((
-            enemy_sound_effect(handle, *transform, stats),
+            enemy_sound_effect(handle, *transform, stats, VoicePriority::Low),
             VocalOf(entity),
         ));
     }
@@ -172,7 +211,11 @@ fn stagger_on_hit(
     let Ok((mut ai_state, stats, transform)) = enemies.get_mut(entity) else {
         return;
     };
-    if !matches!(*ai_state, AiState::Chase) {
+    // Staggering a ranged NPC interrupts its aim.
+    if !matches!(
+        *ai_state,
+        AiState::Chase | AiState::Reposition(..) | AiState::Aim { .. }
+    ) {
         return;
     }
 
@@ -191,7 +234,7 @@ fn stagger_on_hit(
         );
 
         commands.spawn((
-            enemy_sound_effect(handle, *transform, stats),
+            enemy_sound_effect(handle, *transform, stats, VoicePriority::Normal),
             VocalOf(entity),
         ));
     }
@@ -201,6 +244,7 @@ pub(crate) fn enemy_sound_effect(
     handle: Handle<AudioSource>,
     transform: Transform,
     stats: &NpcStats,
+    priority: VoicePriority,
 ) -> impl Bundle {
     let speed_mod = rand::thread_rng().gen_range(0.9..1.1);
     (
@@ -212,5 +256,6 @@ pub(crate) fn enemy_sound_effect(
             .with_speed(1.0 / stats.size * speed_mod)
             .with_spatial_scale(SpatialScale::new(1.0 / 5.5)),
         SoundEffect,
+        SpatialVoice(priority),
     )
 }
@@ -0,0 +1,136 @@
+//! NPC simulation level of detail. NPCs far away from the player or out of view don't need
+//! the full simulation every frame, so they are sorted into [`LodTier`]s that the NPC systems
+//! use to update them less often or skip work entirely.
+
+use avian3d::prelude::*;
+use bevy::{diagnostic::FrameCount, prelude::*};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    gameplay::{
+        npc::{Npc, ai_state::update_ai_state, attack::Attacking},
+        player::camera::{PlayerCamera, WorldModelFov},
+    },
+    third_party::avian3d::CollisionLayer,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(NpcLod, LodTier, NpcLodSettings)>();
+    app.init_resource::<NpcLodSettings>();
+    app.add_systems(PreUpdate, update_npc_lod.before(update_ai_state));
+    app.add_observer(init_npc_lod);
+}
+
+/// How much of the simulation an NPC gets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Reflect)]
+pub(crate) enum LodTier {
+    /// Everything, every frame.
+    #[default]
+    Full,
+    /// AI and animation state are only updated every few frames, and the NPC doesn't grunt.
+    Reduced,
+    /// Like [`LodTier::Reduced`], but even less often. Animations are stopped, and with
+    /// [`NpcLodSettings::simplify_physics`], the NPC stops colliding with other NPCs.
+    Minimal,
+}
+
+impl LodTier {
+    /// The number of frames between updates of throttled systems.
+    fn update_interval(self) -> u32 {
+        match self {
+            LodTier::Full => 1,
+            LodTier::Reduced => 4,
+            LodTier::Minimal => 12,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub(crate) struct NpcLod {
+    pub(crate) tier: LodTier,
+    /// Offsets the frames on which this NPC is updated so that throttled NPCs don't all update on the same frame.
+    phase: u32,
+}
+
+impl NpcLod {
+    /// Whether systems that are throttled by LOD should update this NPC this frame.
+    pub(crate) fn should_update(&self, frame: &FrameCount) -> bool {
+        frame.0.wrapping_add(self.phase) % self.tier.update_interval() == 0
+    }
+}
+
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct NpcLodSettings {
+    /// NPCs closer to the player than this always get the full simulation.
+    pub(crate) full_distance: f32,
+    /// NPCs in view get the full simulation up to this distance, and the ones out of view a reduced one.
+    /// Beyond it, NPCs in view get a reduced simulation and the ones out of view a minimal one.
+    pub(crate) reduced_distance: f32,
+    /// Whether NPCs in the minimal tier stop colliding with each other.
+    pub(crate) simplify_physics: bool,
+}
+
+impl Default for NpcLodSettings {
+    fn default() -> Self {
+        Self {
+            full_distance: 20.0,
+            reduced_distance: 50.0,
+            simplify_physics: true,
+        }
+    }
+}
+
+fn init_npc_lod(trigger: Trigger<OnAdd, Npc>, mut commands: Commands) {
+    let entity = trigger.target();
+    commands.entity(entity).insert(NpcLod {
+        tier: LodTier::Full,
+        phase: entity.index(),
+    });
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_npc_lod(
+    mut npcs: Query<(
+        &mut NpcLod,
+        &Transform,
+        &mut CollisionLayers,
+        Has<Attacking>,
+    )>,
+    camera: Single<&Transform, (With<PlayerCamera>, Without<NpcLod>)>,
+    fov: Res<WorldModelFov>,
+    settings: Res<NpcLodSettings>,
+) {
+    // The FOV is vertical, so the full angle is a generous estimate of half the horizontal one on wide screens.
+    let view_angle = fov.to_radians();
+    for (mut lod, transform, mut layers, attacking) in &mut npcs {
+        let to_npc = transform.translation - camera.translation;
+        let distance = to_npc.length();
+        let in_view = camera.forward().angle_between(to_npc) < view_angle;
+        let tier = if distance < settings.full_distance
+            || (in_view && distance < settings.reduced_distance)
+        {
+            LodTier::Full
+        } else if in_view || distance < settings.reduced_distance || attacking {
+            // Attacks end with their animation, so attacking NPCs must keep animating.
+            LodTier::Reduced
+        } else {
+            LodTier::Minimal
+        };
+        if tier == lod.tier {
+            continue;
+        }
+
+        // Every character is on the character layer, so we need to filter that too.
+        // The player is still hit through the player layer.
+        let npc_layers = [CollisionLayer::Character, CollisionLayer::Npc];
+        if tier == LodTier::Minimal && settings.simplify_physics {
+            layers.filters.remove(npc_layers);
+        } else {
+            layers.filters.add(npc_layers);
+        }
+        lod.tier = tier;
+    }
+}
@@ -13,35 +13,49 @@ use bevy_trenchbroom::prelude::*;
 use crate::{
     gameplay::{
         explosion::{ExplodeOnDeath, Explosive},
-        npc::stats::NpcStats,
+        npc::{dismemberment::Limbs, kamikaze::Kamikaze, stats::NpcStats},
+        surface::SurfaceMaterial,
     },
     third_party::{avian3d::CollisionLayer, bevy_trenchbroom::LoadTrenchbroomModel as _},
 };
 
-use super::{animation::AnimationPlayerAncestor, health::Health};
+use super::animation::AnimationPlayerAncestor;
 pub(crate) mod ai_state;
 mod animation;
 mod assets;
-mod attack;
-pub(crate) mod despawn_hacks;
+pub(crate) mod attack;
+pub(crate) mod attack_slots;
+pub(crate) mod dismemberment;
+pub(crate) mod kamikaze;
 pub(crate) mod lifecycle;
+pub(crate) mod lod;
+pub(crate) mod nav_link;
 pub(crate) mod navigation;
+pub(crate) mod perception;
+pub(crate) mod ragdoll;
+pub(crate) mod ranged;
 mod sound;
 pub(crate) mod stats;
+pub(crate) mod stuck;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         navigation::plugin,
+        nav_link::plugin,
         animation::plugin,
         assets::plugin,
         sound::plugin,
         ai_state::plugin,
         attack::plugin,
+        attack_slots::plugin,
+        dismemberment::plugin,
+        ranged::plugin,
+        kamikaze::plugin,
+        perception::plugin,
         lifecycle::plugin,
-        stats::plugin,
-        despawn_hacks::plugin,
     ));
-    app.register_type::<Npc>();
+    app.add_plugins((lod::plugin, ragdoll::plugin, stats::plugin, stuck::plugin));
+    app.register_type::<(Npc, NpcModel)>();
     app.add_observer(on_add);
 }
 
@@ -49,11 +63,16 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(QuakeClass, Component)]
 #[base(Transform, Visibility)]
 #[model("models/zombie_3/zombie_3.gltf")]
-#[require(NpcStats)]
+#[require(NpcStats, SurfaceMaterial = SurfaceMaterial::Flesh)]
 // In Wasm, TrenchBroom classes are not automatically registered.
 // So, we need to manually register the class in `src/third_party/bevy_trenchbroom/mod.rs`.
 pub(crate) struct Npc;
 
+/// The visual model of an NPC, a child of the entity with the [`Npc`] component.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) struct NpcModel;
+
 pub(crate) const NPC_RADIUS: f32 = 0.4;
 pub(crate) const NPC_CAPSULE_LENGTH: f32 = 0.6;
 pub(crate) const NPC_HEIGHT: f32 = NPC_CAPSULE_LENGTH + 2.0 * NPC_RADIUS;
@@ -61,13 +80,26 @@ pub(crate) const NPC_HEIGHT: f32 = NPC_CAPSULE_LENGTH + 2.0 * NPC_RADIUS;
 #[cfg_attr(feature = "hot_patch", hot)]
 fn on_add(
     trigger: Trigger<OnAdd, NpcStats>,
-    stats: Query<&NpcStats>,
+    stats: Query<(&NpcStats, Has<Kamikaze>)>,
     mut commands: Commands,
     assets: Res<AssetServer>,
 ) {
-    let Ok(stats) = stats.get(trigger.target()) else {
+    let Ok((stats, is_kamikaze)) = stats.get(trigger.target()) else {
         return;
     };
+    let explosive = if is_kamikaze {
+        Kamikaze::explosive(stats)
+    } else {
+        Explosive {
+            radius: stats.size * 2.5,
+            impulse_strength: 5.0,
+            // Scale the damage based on the NPC size
+            // so that killing a larger NPC is more impactful.
+            damage: stats.size * 75.0,
+            damages_player: false,
+            status_effect: None,
+        }
+    };
     let radius = stats.radius();
     let capsule_length = stats.capsule_length();
     let npc_float_height = stats.float_height();
@@ -87,22 +119,20 @@ fn on_add(
                 [CollisionLayer::Character, CollisionLayer::Npc],
                 LayerMask::ALL,
             ),
-            Health::new(100.0),
-            AiState::default(),
             ExplodeOnDeath,
-            Explosive {
-                radius: stats.size * 2.5,
-                impulse_strength: 5.0,
-                // Scale the damage based on the NPC size
-                // so that killing a larger NPC is more impactful.
-                damage: stats.size * 75.0,
-                damages_player: false,
-            },
+            explosive,
+            Limbs::new(stats),
         ))
         .with_child((
             Name::new("Npc Model"),
+            NpcModel,
             SceneRoot(assets.load_trenchbroom_model::<Npc>()),
             Transform::from_xyz(0.0, -npc_float_height, 0.0).with_scale(Vec3::splat(stats.size)),
         ))
         .observe(setup_npc_animations);
+    // Regular NPCs start out chasing the player. Perceptive NPCs get `AiState::Wander`
+    // from `perception::start_wandering` instead, which this must not overwrite.
+    commands
+        .entity(trigger.target())
+        .insert_if_new(AiState::default());
 }
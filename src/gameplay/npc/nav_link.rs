@@ -0,0 +1,603 @@
+//! Off-mesh links that let NPCs jump gaps, drop off ledges and climb up to ledges
+//! between parts of the navmesh that are not connected.
+//!
+//! Our version of landmass can't path across links by itself, so we route around it:
+//! every now and then, a moving NPC compares the cheapest link route to its target with the path landmass
+//! would take, which counts as infinitely long if there is none. If the link wins, the NPC walks to its start,
+//! gets across by jumping, climbing or walking off the edge, and then lets landmass take over again.
+//!
+//! Links are placed in TrenchBroom as a [`NavLink`] targeting a [`NavLinkEnd`].
+//! Drop links are also generated on the fly when an NPC has no path and there is a ledge between it and its target.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_landmass::{AgentState, Archipelago3d, prelude::AgentTarget3d};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use bevy_tnua::{builtins::TnuaBuiltinJumpState, prelude::*};
+use bevy_trenchbroom::prelude::*;
+
+use crate::{
+    PrePhysicsAppSystems,
+    gameplay::{
+        npc::{
+            Npc,
+            ai_state::AiState,
+            navigation::{
+                Agent, NPC_MAX_SLOPE, navmesh_path_length, sample_navmesh, update_agent_target,
+            },
+            stats::NpcStats,
+        },
+        status_effects::StatusEffects,
+    },
+    screens::Screen,
+    third_party::{avian3d::CollisionLayer, bevy_landmass::BaseArchipelago},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(
+        NavLink,
+        NavLinkEnd,
+        NavLinkKind,
+        OffMeshLink,
+        GeneratedNavLink,
+        NavLinkRoute,
+        NavLinkMemory,
+    )>();
+    app.add_systems(Update, resolve_placed_links);
+    app.add_systems(
+        RunFixedMainLoop,
+        route_through_nav_links
+            .after(update_agent_target)
+            .in_set(PrePhysicsAppSystems::UpdateNavmeshTargets),
+    );
+    app.add_systems(
+        RunFixedMainLoop,
+        traverse_nav_links.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+    );
+    app.add_observer(init_nav_link_memory);
+}
+
+/// How far away from an NPC the start of a link may be for the NPC to consider it.
+const MAX_LINK_DISTANCE: f32 = 30.0;
+/// How close an NPC must get to the start of a link to begin traversing it, relative to its size.
+const LINK_ENTRY_RADIUS: f32 = 1.2;
+/// How close to the end of a link an NPC must get for the traversal to count as done.
+const LINK_EXIT_RADIUS: f32 = 0.8;
+/// How often a moving NPC looks for a link to use.
+const LINK_SEARCH_SECS: f32 = 1.0;
+/// How much longer than the actual distance getting across a link counts as,
+/// so that NPCs only leave the navmesh when it saves them a real detour.
+const LINK_TRAVERSAL_PENALTY: f32 = 3.0;
+/// How long an NPC may take to reach the start of a link before giving up on it.
+const APPROACH_TIMEOUT_SECS: f32 = 10.0;
+/// How long an NPC may take to get across a link before giving up on it.
+const TRAVERSE_TIMEOUT_SECS: f32 = 4.0;
+/// Links going down more than this are drops.
+const MIN_DROP_HEIGHT: f32 = 1.0;
+/// Links going up more than this are climbs.
+const MIN_CLIMB_HEIGHT: f32 = 1.5;
+/// How far above the higher end of a link NPCs jump or climb.
+const JUMP_CLEARANCE: f32 = 0.8;
+const CLIMB_CLEARANCE: f32 = 0.4;
+/// Climbing is slower than running, relative to the NPC's desired speed.
+/// This applies both to how fast they move towards the ledge and how fast they rise.
+const CLIMB_SPEED_FACTOR: f32 = 0.4;
+/// How far from an NPC generated drop links are searched for.
+const DROP_SEARCH_DISTANCE: f32 = 6.0;
+const DROP_SEARCH_STEP: f32 = 0.5;
+/// The highest ledge NPCs drop off.
+const MAX_DROP_HEIGHT: f32 = 8.0;
+/// The directions around the one towards the target in which drop links are searched, in degrees.
+const DROP_SEARCH_ANGLES: [f32; 5] = [0.0, -30.0, 30.0, -60.0, 60.0];
+
+/// The start of an off-mesh link. Set `target` to the `targetname` of a [`NavLinkEnd`].
+/// Place both on the floor, the start right at the edge the NPCs should jump, drop or climb from.
+#[derive(PointClass, Component, Debug, Clone, Default, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+pub(crate) struct NavLink {
+    pub(crate) target: String,
+    pub(crate) kind: NavLinkKind,
+    /// Whether NPCs may also use the link from the end to the start.
+    pub(crate) bidirectional: bool,
+}
+
+/// The end of an off-mesh link started by a [`NavLink`].
+#[derive(PointClass, Component, Debug, Clone, Default, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+pub(crate) struct NavLinkEnd {
+    pub(crate) targetname: String,
+}
+
+/// How NPCs get across a link.
+#[derive(FgdType, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub(crate) enum NavLinkKind {
+    /// Pick the kind from the height difference between the start and the end.
+    #[default]
+    Auto,
+    /// Jump across a gap.
+    Jump,
+    /// Walk off a ledge and fall down.
+    Drop,
+    /// Scramble up to a ledge.
+    Climb,
+}
+
+impl NavLinkKind {
+    /// Resolves [`NavLinkKind::Auto`] for a link going from `start` to `end`.
+    fn resolve(self, start: Vec3, end: Vec3) -> Self {
+        if self != NavLinkKind::Auto {
+            return self;
+        }
+        let height = end.y - start.y;
+        if height < -MIN_DROP_HEIGHT {
+            NavLinkKind::Drop
+        } else if height > MIN_CLIMB_HEIGHT {
+            NavLinkKind::Climb
+        } else {
+            NavLinkKind::Jump
+        }
+    }
+}
+
+/// A one-way link that NPCs can use, with its endpoints in world space.
+/// Placed links spawn one of these for every direction they can be used in.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub(crate) struct OffMeshLink {
+    pub(crate) start: Vec3,
+    pub(crate) end: Vec3,
+    pub(crate) kind: NavLinkKind,
+}
+
+impl OffMeshLink {
+    fn new(start: Vec3, end: Vec3, kind: NavLinkKind) -> Self {
+        Self {
+            start,
+            end,
+            kind: kind.resolve(start, end),
+        }
+    }
+}
+
+/// An [`OffMeshLink`] that was generated at runtime instead of placed in TrenchBroom.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) struct GeneratedNavLink;
+
+/// An NPC on its way across an [`OffMeshLink`].
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct NavLinkRoute {
+    link: Entity,
+    pub(crate) start: Vec3,
+    pub(crate) end: Vec3,
+    pub(crate) kind: NavLinkKind,
+    pub(crate) phase: RoutePhase,
+    /// Times out the current phase.
+    timer: Timer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub(crate) enum RoutePhase {
+    /// Walking to the start of the link.
+    Approach,
+    /// Jumping, dropping or climbing to the end of the link.
+    Traverse { launched: bool, left_ground: bool },
+}
+
+impl NavLinkRoute {
+    fn new(link: Entity, off_mesh_link: &OffMeshLink) -> Self {
+        Self {
+            link,
+            start: off_mesh_link.start,
+            end: off_mesh_link.end,
+            kind: off_mesh_link.kind,
+            phase: RoutePhase::Approach,
+            timer: Timer::from_seconds(APPROACH_TIMEOUT_SECS, TimerMode::Once),
+        }
+    }
+
+    pub(crate) fn is_traversing(&self) -> bool {
+        matches!(self.phase, RoutePhase::Traverse { .. })
+    }
+
+    fn start_traversing(&mut self) {
+        self.phase = RoutePhase::Traverse {
+            launched: false,
+            left_ground: false,
+        };
+        self.timer = Timer::from_seconds(TRAVERSE_TIMEOUT_SECS, TimerMode::Once);
+    }
+
+    fn jump_height(&self) -> f32 {
+        (self.end.y - self.start.y).max(0.0) + JUMP_CLEARANCE
+    }
+
+    /// How far a climbing NPC has to be lifted from where it started to get over the ledge.
+    fn climb_height(&self) -> f32 {
+        (self.end.y - self.start.y).max(0.0) + CLIMB_CLEARANCE
+    }
+}
+
+/// What an NPC remembers about links while it is moving.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct NavLinkMemory {
+    /// Links the NPC could not get across since it last crossed one or stood still, so that it doesn't keep trying them.
+    failed: Vec<Entity>,
+    search_timer: Timer,
+}
+
+impl Default for NavLinkMemory {
+    fn default() -> Self {
+        Self {
+            failed: Vec::new(),
+            search_timer: Timer::from_seconds(LINK_SEARCH_SECS, TimerMode::Once),
+        }
+    }
+}
+
+fn init_nav_link_memory(trigger: Trigger<OnAdd, Npc>, mut commands: Commands) {
+    commands
+        .entity(trigger.target())
+        .insert(NavLinkMemory::default());
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn resolve_placed_links(
+    links: Query<(Entity, &NavLink, &Transform), Added<NavLink>>,
+    ends: Query<(&NavLinkEnd, &Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, link, transform) in &links {
+        let Some((_, end_transform)) = ends.iter().find(|(end, _)| end.targetname == link.target)
+        else {
+            warn!(
+                "Nav link at {} targets \"{}\", but there is no nav link end with that targetname",
+                transform.translation, link.target
+            );
+            continue;
+        };
+        let start = transform.translation;
+        let end = end_transform.translation;
+        commands.spawn((
+            Name::new("Off-Mesh Link"),
+            OffMeshLink::new(start, end, link.kind),
+            ChildOf(entity),
+        ));
+        if link.bidirectional {
+            commands.spawn((
+                Name::new("Off-Mesh Link (Reverse)"),
+                OffMeshLink::new(end, start, link.kind),
+                ChildOf(entity),
+            ));
+        }
+    }
+}
+
+/// Sends NPCs to the start of a link when that is cheaper than their path on the navmesh,
+/// and starts the traversal once they get there.
+#[cfg_attr(feature = "hot_patch", hot)]
+fn route_through_nav_links(
+    mut npcs: Query<(
+        Entity,
+        &Transform,
+        &NpcStats,
+        &AiState,
+        &Agent,
+        &mut NavLinkMemory,
+        Option<&mut NavLinkRoute>,
+    )>,
+    mut agents: Query<(&AgentState, &mut AgentTarget3d)>,
+    links: Query<(Entity, &OffMeshLink)>,
+    archipelago: Single<&Archipelago3d, With<BaseArchipelago>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, transform, stats, ai_state, agent, mut memory, route) in &mut npcs {
+        let Ok((agent_state, mut target)) = agents.get_mut(**agent) else {
+            continue;
+        };
+        let feet = transform.translation - Vec3::Y * stats.float_height();
+        let wants_to_move = matches!(
+            ai_state,
+            AiState::Chase
+                | AiState::Reposition(..)
+                | AiState::Wander(..)
+                | AiState::Investigate(..)
+        );
+
+        if let Some(mut route) = route {
+            route.timer.tick(time.delta());
+            match route.phase {
+                RoutePhase::Approach => {
+                    let entry_radius = LINK_ENTRY_RADIUS * stats.size.max(1.0);
+                    // The agent state still belongs to the old target right after switching to the link.
+                    let unreachable = matches!(agent_state, AgentState::NoPath)
+                        && route.timer.elapsed_secs() > LINK_SEARCH_SECS;
+                    if feet.distance(route.start) < entry_radius {
+                        route.start_traversing();
+                    } else if unreachable || route.timer.finished() {
+                        memory.failed.push(route.link);
+                        commands.entity(entity).remove::<NavLinkRoute>();
+                        continue;
+                    }
+                    // Staggered NPCs keep their route, but stay where they are for now.
+                    if wants_to_move {
+                        *target = AgentTarget3d::Point(route.start);
+                    }
+                }
+                // Let landmass plan the path from the end of the link while we are on the way there.
+                RoutePhase::Traverse { .. } => {
+                    *target = AgentTarget3d::Point(route.end);
+                }
+            }
+            continue;
+        }
+
+        if !wants_to_move {
+            memory.failed.clear();
+            memory.search_timer.reset();
+            continue;
+        }
+        let AgentTarget3d::Point(goal) = *target else {
+            continue;
+        };
+        memory.search_timer.tick(time.delta());
+        if !memory.search_timer.finished() {
+            continue;
+        }
+        memory.search_timer.reset();
+
+        // Only links that actually bring the NPC closer to its goal are worth considering.
+        let remaining = feet.distance(goal);
+        let mut candidates: Vec<_> = links
+            .iter()
+            .filter(|(link, _)| !memory.failed.contains(link))
+            .filter(|(_, link)| {
+                feet.distance(link.start) < MAX_LINK_DISTANCE && link.end.distance(goal) < remaining
+            })
+            .map(|(link_entity, link)| {
+                let cost = feet.distance(link.start) + link_cost(link, link.end.distance(goal));
+                (link_entity, link, cost)
+            })
+            .collect();
+        let no_path = matches!(agent_state, AgentState::NoPath);
+        if candidates.is_empty() && !no_path {
+            continue;
+        }
+        let path_length = if no_path {
+            f32::INFINITY
+        } else {
+            let Some(path_length) = navmesh_path_length(&archipelago, feet, goal) else {
+                continue;
+            };
+            path_length
+        };
+
+        // The straight-line costs are lower bounds, so we can stop as soon as one of them can't beat the best route so far.
+        candidates.sort_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+        let mut best_link = None;
+        let mut best_cost = path_length;
+        for (link_entity, link, lower_bound) in candidates {
+            if lower_bound >= best_cost {
+                break;
+            }
+            // Legs that landmass can't find a path for count as straight lines,
+            // and if the NPC can't actually get there, the approach times out.
+            let approach = navmesh_path_length(&archipelago, feet, link.start)
+                .unwrap_or_else(|| feet.distance(link.start));
+            let rest = navmesh_path_length(&archipelago, link.end, goal)
+                .unwrap_or_else(|| link.end.distance(goal));
+            let cost = approach + link_cost(link, rest);
+            if cost < best_cost {
+                best_link = Some((link_entity, link));
+                best_cost = cost;
+            }
+        }
+        if let Some((link_entity, link)) = best_link {
+            commands
+                .entity(entity)
+                .insert(NavLinkRoute::new(link_entity, link));
+            continue;
+        }
+        if !no_path {
+            continue;
+        }
+
+        let Some(link) = find_drop_link(feet, goal, stats, &archipelago, &spatial_query, entity)
+        else {
+            continue;
+        };
+        // Other NPCs will run into the same ledge, so reuse a generated link if there already is one.
+        let existing = links.iter().find(|(_, existing)| {
+            existing.start.distance(link.start) < 1.0 && existing.end.distance(link.end) < 1.0
+        });
+        let link_entity = match existing {
+            Some((link_entity, _)) => link_entity,
+            None => {
+                info!("Generated a drop link from {} to {}", link.start, link.end);
+                commands
+                    .spawn((
+                        Name::new("Generated Off-Mesh Link"),
+                        link,
+                        GeneratedNavLink,
+                        StateScoped(Screen::Gameplay),
+                    ))
+                    .id()
+            }
+        };
+        if memory.failed.contains(&link_entity) {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .insert(NavLinkRoute::new(link_entity, &link));
+    }
+}
+
+/// The cost of getting across a link and then walking `rest` to the goal.
+fn link_cost(link: &OffMeshLink, rest: f32) -> f32 {
+    link.start.distance(link.end) + LINK_TRAVERSAL_PENALTY + rest
+}
+
+/// Looks for a ledge between `feet` and `goal` that an NPC can drop down from onto the navmesh below.
+fn find_drop_link(
+    feet: Vec3,
+    goal: Vec3,
+    stats: &NpcStats,
+    archipelago: &Archipelago3d,
+    spatial_query: &SpatialQuery,
+    npc: Entity,
+) -> Option<OffMeshLink> {
+    if goal.y > feet.y - MIN_DROP_HEIGHT {
+        return None;
+    }
+    let forward = Dir3::new((goal - feet).with_y(0.0)).ok()?;
+    let filter = SpatialQueryFilter::default()
+        .with_mask(CollisionLayer::Default)
+        .with_excluded_entities([npc]);
+    let steps = (DROP_SEARCH_DISTANCE / DROP_SEARCH_STEP) as usize;
+
+    for angle in DROP_SEARCH_ANGLES {
+        let dir = Quat::from_rotation_y(angle.to_radians()) * forward;
+        let mut ledge = None;
+        for step in 1..=steps {
+            let distance = step as f32 * DROP_SEARCH_STEP;
+            let probe = feet + dir * distance;
+            // Sampling snaps points back onto the navmesh, so a point that moved is past the edge.
+            let on_mesh = sample_navmesh(archipelago, probe).is_some_and(|point| {
+                point.xz().distance(probe.xz()) < DROP_SEARCH_STEP * 0.5
+                    && (point.y - feet.y).abs() < MIN_DROP_HEIGHT * 0.5
+            });
+            if on_mesh {
+                ledge = Some(probe);
+                continue;
+            }
+            let Some(start) = ledge else {
+                break;
+            };
+            // Make sure there is no wall between the NPC and the ledge.
+            let eyes = feet + Vec3::Y * stats.half_height();
+            if spatial_query
+                .cast_ray(eyes, dir, distance, true, &filter)
+                .is_some()
+            {
+                break;
+            }
+            let above = probe + Vec3::Y * stats.half_height();
+            let Some(hit) =
+                spatial_query.cast_ray(above, Dir3::NEG_Y, MAX_DROP_HEIGHT, true, &filter)
+            else {
+                break;
+            };
+            let ground = above - Vec3::Y * hit.distance;
+            if feet.y - ground.y < MIN_DROP_HEIGHT
+                || hit.normal.angle_between(Vec3::Y) > NPC_MAX_SLOPE
+            {
+                break;
+            }
+            // Land a bit away from the wall below the ledge.
+            let Some(end) = sample_navmesh(archipelago, ground + dir * stats.radius() * 2.0)
+                .filter(|end| (end.y - ground.y).abs() < MIN_DROP_HEIGHT * 0.5)
+            else {
+                break;
+            };
+            return Some(OffMeshLink::new(start, end, NavLinkKind::Drop));
+        }
+    }
+    None
+}
+
+/// Moves NPCs across the link they are traversing, taking over from the regular navmesh movement.
+#[cfg_attr(feature = "hot_patch", hot)]
+fn traverse_nav_links(
+    mut npcs: Query<(
+        Entity,
+        &Transform,
+        &NpcStats,
+        &mut TnuaController,
+        &mut NavLinkRoute,
+        &mut NavLinkMemory,
+        Option<&StatusEffects>,
+    )>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, transform, stats, mut controller, mut route, mut memory, status_effects) in
+        &mut npcs
+    {
+        let RoutePhase::Traverse {
+            mut launched,
+            mut left_ground,
+        } = route.phase
+        else {
+            continue;
+        };
+        route.timer.tick(time.delta());
+        let feet = transform.translation - Vec3::Y * stats.float_height();
+        let to_end = route.end - feet;
+        let horizontal = to_end.with_y(0.0);
+        let airborne = controller.is_airborne().unwrap_or(false);
+        left_ground |= airborne;
+
+        let arrived = horizontal.length() < LINK_EXIT_RADIUS && to_end.y.abs() < MIN_DROP_HEIGHT;
+        if arrived || (left_ground && !airborne) {
+            memory.failed.clear();
+            commands.entity(entity).remove::<NavLinkRoute>();
+        } else if route.timer.finished() {
+            warn!(
+                "NPC at {} could not get across the link from {} to {}",
+                transform.translation, route.start, route.end
+            );
+            memory.failed.push(route.link);
+            commands.entity(entity).remove::<NavLinkRoute>();
+        }
+
+        let speed_factor = status_effects.map_or(1.0, StatusEffects::speed_factor);
+        let speed = match route.kind {
+            NavLinkKind::Climb => stats.desired_speed * CLIMB_SPEED_FACTOR,
+            _ => stats.max_speed,
+        } * speed_factor;
+        let stunned = status_effects.is_some_and(StatusEffects::is_stunned);
+        // Climbers are hoisted up the wall by floating higher and higher until their feet clear the ledge.
+        // Then they fall back to their regular float height and the ledge catches them.
+        let lift = if route.kind == NavLinkKind::Climb && !stunned && feet.y < route.end.y {
+            (route.timer.elapsed_secs() * speed).min(route.climb_height())
+        } else {
+            0.0
+        };
+        // Slow down when close so that we don't overshoot the end.
+        let velocity = (horizontal * 2.0).clamp_length_max(speed);
+        controller.basis(TnuaBuiltinWalk {
+            desired_velocity: velocity,
+            desired_forward: Dir3::try_from(horizontal).ok(),
+            float_height: stats.float_height() + lift,
+            spring_strength: 1500.0,
+            max_slope: NPC_MAX_SLOPE,
+            ..default()
+        });
+
+        if route.kind == NavLinkKind::Jump && !stunned {
+            let falling = controller
+                .concrete_action::<TnuaBuiltinJump>()
+                .map(|(_, state)| matches!(state, TnuaBuiltinJumpState::FallSection));
+            // Keep holding the jump until it starts falling so that it reaches its full height.
+            if !launched || falling == Some(false) {
+                controller.action(TnuaBuiltinJump {
+                    height: route.jump_height(),
+                    ..default()
+                });
+            }
+            launched |= falling.is_some();
+        }
+        route.phase = RoutePhase::Traverse {
+            launched,
+            left_ground,
+        };
+    }
+}
@@ -3,9 +3,9 @@
 use std::f32::consts::TAU;
 
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{diagnostic::FrameCount, prelude::*};
 use bevy_landmass::{
-    TargetReachedCondition,
+    PointSampleDistance3d, TargetReachedCondition,
     prelude::{
         AgentDesiredVelocity3d as LandmassAgentDesiredVelocity, Velocity3d as LandmassVelocity, *,
     },
@@ -16,12 +16,25 @@ use bevy_tnua::prelude::*;
 
 use crate::{
     PrePhysicsAppSystems,
-    gameplay::{npc::stats::NpcStats, player::navmesh_position::LastValidPlayerNavmeshPosition},
+    gameplay::{
+        npc::stats::NpcStats, player::navmesh_position::LastValidPlayerNavmeshPosition,
+        status_effects::StatusEffects,
+    },
+    third_party::bevy_landmass::NavTier,
 };
 
-use super::{ai_state::AiState, attack::Attacking};
+use super::{
+    ai_state::AiState,
+    attack::Attacking,
+    attack_slots::AttackSlot,
+    dismemberment::{CRAWL_SPEED_FACTOR, Crawler},
+    lod::NpcLod,
+    nav_link::NavLinkRoute,
+};
 
 pub(crate) const NPC_MAX_SLOPE: f32 = TAU / 6.0;
+/// Wandering NPCs shamble instead of running.
+const WANDER_SPEED_FACTOR: f32 = 0.3;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Agent>();
@@ -48,12 +61,24 @@ fn setup_npc_agent(
     trigger: Trigger<OnAdd, NpcStats>,
     stats: Query<&NpcStats>,
     mut commands: Commands,
-    archipelago: Single<Entity, With<Archipelago3d>>,
+    tiers: Query<(Entity, &NavTier)>,
 ) {
     let npc = trigger.target();
     let Ok(stats) = stats.get(npc) else {
         return;
     };
+    // Use the smallest tier the NPC fits into, or the largest one if it is too big for all of them.
+    let mut tiers = tiers.iter().collect::<Vec<_>>();
+    tiers.sort_by(|(_, a), (_, b)| a.agent_radius.total_cmp(&b.agent_radius));
+    let Some((archipelago, _)) = tiers
+        .iter()
+        .find(|(_, tier)| stats.radius() <= tier.agent_radius + f32::EPSILON)
+        .or(tiers.last())
+        .copied()
+    else {
+        error!("No navigation tiers to put the NPC agent into");
+        return;
+    };
     commands.spawn((
         Name::new("NPC Agent"),
         Transform::from_translation(Vec3::new(0.0, -stats.float_height(), 0.0)),
@@ -64,7 +89,7 @@ fn setup_npc_agent(
                 desired_speed: stats.desired_speed,
                 max_speed: stats.max_speed,
             },
-            archipelago_ref: ArchipelagoRef3d::new(*archipelago),
+            archipelago_ref: ArchipelagoRef3d::new(archipelago),
         },
         TargetReachedCondition::Distance(Some(1.5 * stats.size)),
         ChildOf(npc),
@@ -79,23 +104,29 @@ fn setup_npc_agent(
 struct WantsToFollowPlayer;
 
 #[cfg_attr(feature = "hot_patch", hot)]
-fn update_agent_target(
+pub(super) fn update_agent_target(
     mut agents: Query<(&mut AgentTarget3d, &AgentOf), With<WantsToFollowPlayer>>,
-    ai_state: Query<(&Transform, &AiState)>,
+    ai_state: Query<(&Transform, &AiState, Option<&AttackSlot>)>,
     player_position: Single<&LastValidPlayerNavmeshPosition>,
 ) {
     let Some(player_position) = player_position.0 else {
         return;
     };
     for (mut target, agent_of) in &mut agents {
-        let Ok((ai_transform, ai_state)) = ai_state.get(agent_of.0) else {
+        let Ok((ai_transform, ai_state, attack_slot)) = ai_state.get(agent_of.0) else {
             continue;
         };
         match ai_state {
             AiState::Chase => {
-                *target = AgentTarget3d::Point(player_position);
+                let position = attack_slot.map_or(player_position, |slot| slot.position);
+                *target = AgentTarget3d::Point(position);
+            }
+            AiState::Reposition(position)
+            | AiState::Wander(position)
+            | AiState::Investigate(position) => {
+                *target = AgentTarget3d::Point(*position);
             }
-            AiState::Stagger(..) | AiState::Attack => {
+            AiState::Stagger(..) | AiState::Attack | AiState::Aim { .. } => {
                 *target = AgentTarget3d::Point(ai_transform.translation);
             }
         }
@@ -115,16 +146,46 @@ pub(crate) struct Agent(Entity);
 /// Use the desired velocity as the agent's velocity.
 #[cfg_attr(feature = "hot_patch", hot)]
 fn set_controller_velocity(
-    mut agent_query: Query<(&mut TnuaController, &Agent, Option<&Attacking>, &NpcStats)>,
+    mut agent_query: Query<(
+        &mut TnuaController,
+        &Agent,
+        Option<&Attacking>,
+        &AiState,
+        &NpcStats,
+        Option<&StatusEffects>,
+        Has<Crawler>,
+        Option<&NavLinkRoute>,
+        Option<&NpcLod>,
+    )>,
     desired_velocity_query: Query<&LandmassAgentDesiredVelocity>,
+    frame: Res<FrameCount>,
 ) {
-    for (mut controller, agent, attacking, stats) in &mut agent_query {
+    for (mut controller, agent, attacking, ai_state, stats, status_effects, crawling, route, lod) in
+        &mut agent_query
+    {
+        // Traversing a link is handled by `nav_link::traverse_nav_links`.
+        if route.is_some_and(NavLinkRoute::is_traversing) {
+            continue;
+        }
+        // The controller keeps the last basis it was fed until the next update.
+        if lod.is_some_and(|lod| !lod.should_update(&frame)) {
+            continue;
+        }
         let Ok(desired_velocity) = desired_velocity_query.get(**agent) else {
             continue;
         };
-        let velocity = desired_velocity.velocity();
+        let mut speed_factor = status_effects.map_or(1.0, StatusEffects::speed_factor);
+        if matches!(ai_state, AiState::Wander(..)) {
+            speed_factor *= WANDER_SPEED_FACTOR;
+        }
+        if crawling {
+            speed_factor *= CRAWL_SPEED_FACTOR;
+        }
+        let velocity = desired_velocity.velocity() * speed_factor;
         let forward = if let Some(attacking) = attacking {
             attacking.dir
+        } else if let AiState::Aim { dir, .. } = ai_state {
+            *dir
         } else {
             Dir3::try_from(velocity).ok()
         };
@@ -146,3 +207,41 @@ fn sync_agent_velocity(mut agent_query: Query<(&LinearVelocity, &mut LandmassVel
         landmass_velocity.velocity = avian_velocity.0;
     }
 }
+
+/// How far away from the navmesh a point may be for [`sample_navmesh`] to snap it onto it.
+const NAVMESH_SAMPLE_DISTANCE: PointSampleDistance3d = PointSampleDistance3d {
+    horizontal_distance: 2.0,
+    distance_above: 1.0,
+    distance_below: 3.0,
+    vertical_preference_ratio: 2.0,
+};
+
+/// Projects a point onto the navmesh so that NPCs can path to it.
+pub(crate) fn sample_navmesh(archipelago: &Archipelago3d, point: Vec3) -> Option<Vec3> {
+    archipelago
+        .sample_point(point, &NAVMESH_SAMPLE_DISTANCE)
+        .ok()
+        .map(|sampled| sampled.point())
+}
+
+/// The length of the path that landmass finds between two points, or `None` if they are not connected on the navmesh.
+pub(crate) fn navmesh_path_length(
+    archipelago: &Archipelago3d,
+    from: Vec3,
+    to: Vec3,
+) -> Option<f32> {
+    let start = archipelago
+        .sample_point(from, &NAVMESH_SAMPLE_DISTANCE)
+        .ok()?;
+    let end = archipelago
+        .sample_point(to, &NAVMESH_SAMPLE_DISTANCE)
+        .ok()?;
+    let path = archipelago.find_path(&start, &end, &default()).ok()?;
+    let mut previous = start.point();
+    let mut length = 0.0;
+    for waypoint in path {
+        length += previous.distance(waypoint);
+        previous = waypoint;
+    }
+    Some(length)
+}
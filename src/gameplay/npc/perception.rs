@@ -0,0 +1,255 @@
+//! NPC perception. NPCs with [`Perception`] don't know where the player is.
+//! They wander around until they see the player within their view cone or hear a noise,
+//! and go looking for the player when they lose track of them.
+//!
+//! NPCs spawned by waves don't have [`Perception`], so they always chase the player.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_landmass::{AgentState, Archipelago3d};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use bevy_trenchbroom::prelude::*;
+
+use crate::{
+    gameplay::{
+        explosion::OnExplode,
+        health::OnDamage,
+        npc::{
+            Npc,
+            ai_state::{AiState, update_ai_state},
+            navigation::{Agent, sample_navmesh},
+            stats::NpcStats,
+        },
+        player::{Player, gunplay::Shooting, navmesh_position::LastValidPlayerNavmeshPosition},
+    },
+    third_party::{avian3d::CollisionLayer, bevy_landmass::BaseArchipelago},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Perception, PerceptiveNpc)>();
+    app.add_observer(start_wandering);
+    app.add_observer(alert_on_noise);
+    app.add_observer(alert_on_damage);
+    app.add_observer(make_gunshot_noise);
+    app.add_observer(make_explosion_noise);
+    app.add_systems(PreUpdate, update_perception.before(update_ai_state));
+}
+
+/// How far from its current position a wandering NPC picks its next destination.
+const WANDER_RADIUS: f32 = 8.0;
+/// NPCs always notice the player this close to them, no matter where they are looking.
+const PROXIMITY_RADIUS: f32 = 2.0;
+/// How far a gunshot can be heard.
+const GUNSHOT_LOUDNESS: f32 = 40.0;
+/// How far an explosion can be heard.
+const EXPLOSION_LOUDNESS: f32 = 60.0;
+
+/// An NPC that needs to see or hear the player before chasing them.
+/// Place these in TrenchBroom for stealthier sections of a map.
+#[derive(PointClass, Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+#[model("models/zombie_3/zombie_3.gltf")]
+#[require(Npc, Perception)]
+pub(crate) struct PerceptiveNpc;
+
+/// The senses of an NPC and what it remembers about the player.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Perception {
+    /// How far the NPC can see.
+    pub(crate) view_distance: f32,
+    /// The full opening angle of the view cone in degrees.
+    pub(crate) view_angle_degrees: f32,
+    /// A multiplier for how far away noises can be heard.
+    pub(crate) hearing: f32,
+    /// How long the NPC keeps chasing the player after losing sight of them.
+    pub(crate) memory: Timer,
+    /// Where the NPC last saw or heard the player.
+    pub(crate) last_known_position: Option<Vec3>,
+}
+
+impl Default for Perception {
+    fn default() -> Self {
+        Self {
+            view_distance: 25.0,
+            view_angle_degrees: 120.0,
+            hearing: 1.0,
+            memory: Timer::from_seconds(4.0, TimerMode::Once),
+            last_known_position: None,
+        }
+    }
+}
+
+/// An event that is triggered globally when something makes a noise that NPCs can hear.
+#[derive(Event, Debug, Clone, Copy)]
+pub(crate) struct OnNoise {
+    pub(crate) position: Vec3,
+    /// The distance at which NPCs with normal hearing can still hear the noise.
+    pub(crate) loudness: f32,
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn start_wandering(
+    trigger: Trigger<OnAdd, Perception>,
+    transforms: Query<&Transform>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    let translation = transforms
+        .get(entity)
+        .map(|transform| transform.translation)
+        .unwrap_or_default();
+    // The regular NPC setup only inserts an `AiState` if there is none yet.
+    commands.entity(entity).insert(AiState::Wander(translation));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_perception(
+    mut npcs: Query<(
+        Entity,
+        &mut AiState,
+        &mut Perception,
+        &NpcStats,
+        &Transform,
+        &Agent,
+    )>,
+    agent_states: Query<&AgentState>,
+    player: Single<(Entity, &Transform), With<Player>>,
+    player_navmesh_position: Single<&LastValidPlayerNavmeshPosition>,
+    archipelago: Single<&Archipelago3d, With<BaseArchipelago>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+) {
+    let (player_entity, player_transform) = *player;
+    for (entity, mut ai_state, mut perception, stats, transform, agent) in &mut npcs {
+        let eyes = transform.translation + Vec3::Y * stats.half_height() * 0.8;
+        let to_player = player_transform.translation - eyes;
+        let distance = to_player.length();
+        let in_view = distance < PROXIMITY_RADIUS
+            || (distance < perception.view_distance
+                && transform.forward().angle_between(to_player)
+                    < perception.view_angle_degrees.to_radians() / 2.0);
+        let sees_player = in_view
+            && Dir3::new(to_player).is_ok_and(|dir| {
+                let filter = SpatialQueryFilter::default()
+                    .with_mask([CollisionLayer::Default, CollisionLayer::Prop])
+                    .with_excluded_entities([entity, player_entity]);
+                spatial_query
+                    .cast_ray(eyes, dir, distance, true, &filter)
+                    .is_none()
+            });
+
+        if sees_player {
+            perception.memory.reset();
+            perception.last_known_position = player_navmesh_position
+                .0
+                .or(Some(player_transform.translation));
+            if matches!(*ai_state, AiState::Wander(..) | AiState::Investigate(..)) {
+                *ai_state = AiState::Chase;
+            }
+            continue;
+        }
+
+        let agent_state = agent_states.get(**agent).ok();
+        let arrived = matches!(
+            agent_state,
+            Some(AgentState::ReachedTarget | AgentState::TargetNotOnNavMesh | AgentState::NoPath)
+        );
+        match *ai_state {
+            AiState::Chase => {
+                perception.memory.tick(time.delta());
+                if perception.memory.finished() {
+                    *ai_state = match perception.last_known_position {
+                        Some(position) => AiState::Investigate(position),
+                        None => AiState::Wander(transform.translation),
+                    };
+                }
+            }
+            AiState::Investigate(..) if arrived => {
+                // Nothing here, so go back to wandering around.
+                perception.last_known_position = None;
+                *ai_state = AiState::Wander(transform.translation);
+            }
+            AiState::Wander(..) if arrived => {
+                // If the sampled point is not on the navmesh, we just try again next frame.
+                let offset = Circle::new(WANDER_RADIUS).sample_interior(&mut rand::thread_rng());
+                let feet = transform.translation - Vec3::Y * stats.float_height();
+                let candidate = feet + Vec3::new(offset.x, 0.0, offset.y);
+                if let Some(point) = sample_navmesh(&archipelago, candidate) {
+                    *ai_state = AiState::Wander(point);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn alert_on_noise(
+    trigger: Trigger<OnNoise>,
+    mut npcs: Query<(&mut AiState, &mut Perception, &Transform)>,
+    archipelago: Single<&Archipelago3d, With<BaseArchipelago>>,
+) {
+    let noise = trigger.event();
+    let position = sample_navmesh(&archipelago, noise.position).unwrap_or(noise.position);
+    for (mut ai_state, mut perception, transform) in &mut npcs {
+        let range = noise.loudness * perception.hearing;
+        if transform.translation.distance_squared(noise.position) > range * range {
+            continue;
+        }
+        if matches!(*ai_state, AiState::Wander(..) | AiState::Investigate(..)) {
+            perception.last_known_position = Some(position);
+            *ai_state = AiState::Investigate(position);
+        }
+    }
+}
+
+/// Getting hurt by the player gives away where they are.
+#[cfg_attr(feature = "hot_patch", hot)]
+fn alert_on_damage(
+    trigger: Trigger<OnDamage>,
+    mut npcs: Query<(&mut AiState, &mut Perception)>,
+    player: Single<Entity, With<Player>>,
+    player_navmesh_position: Single<&LastValidPlayerNavmeshPosition>,
+) {
+    if trigger.event().instigator != Some(*player) {
+        return;
+    }
+    let Ok((mut ai_state, mut perception)) = npcs.get_mut(trigger.target()) else {
+        return;
+    };
+    perception.memory.reset();
+    perception.last_known_position = player_navmesh_position.0;
+    if matches!(*ai_state, AiState::Wander(..) | AiState::Investigate(..)) {
+        *ai_state = AiState::Chase;
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn make_gunshot_noise(
+    _trigger: Trigger<OnAdd, Shooting>,
+    player: Single<&Transform, With<Player>>,
+    mut commands: Commands,
+) {
+    commands.trigger(OnNoise {
+        position: player.translation,
+        loudness: GUNSHOT_LOUDNESS,
+    });
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn make_explosion_noise(
+    trigger: Trigger<OnExplode>,
+    transforms: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    let Ok(transform) = transforms.get(trigger.target()) else {
+        return;
+    };
+    commands.trigger(OnNoise {
+        position: transform.translation(),
+        loudness: EXPLOSION_LOUDNESS,
+    });
+}
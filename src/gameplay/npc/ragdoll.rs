@@ -0,0 +1,314 @@
+//! Ragdoll deaths. Instead of bursting into gibs, a dying NPC can leave behind a copy of its model
+//! whose skeleton is driven by physics.
+//!
+//! Every sufficiently long bone of the skinned mesh becomes a capsule rigid body,
+//! connected to the body of its parent bone with a spherical joint.
+//! Skinning only cares about the global transforms of the joints,
+//! so the bodies are simply the joint entities themselves, detached from their parents.
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    render::{mesh::skinning::SkinnedMesh, view::NoFrustumCulling},
+    scene::SceneInstanceReady,
+};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    PostPhysicsAppSystems,
+    despawn_after::FadeOutAndDespawn,
+    gameplay::{
+        gore_settings::GoreSettings,
+        health::{DamageKind, OnDeath},
+        npc::{assets::NpcAssets, stats::NpcStats},
+        surface::SurfaceMaterial,
+    },
+    screens::{Screen, loading::LoadingScreen},
+    third_party::avian3d::CollisionLayer,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Ragdoll, RagdollPartOf, RagdollParts)>();
+    app.add_systems(
+        Update,
+        enforce_ragdoll_budget
+            .in_set(PostPhysicsAppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Bones shorter than this, relative to the NPC size, don't get their own body and just follow their parent.
+const MIN_BONE_LENGTH: f32 = 0.12;
+/// The radius of a bone's capsule, relative to the NPC size.
+const BONE_RADIUS: f32 = 0.07;
+/// The limits on how far a bone can swing and twist relative to its parent, in radians.
+const SWING_LIMIT: f32 = 1.2;
+const TWIST_LIMIT: f32 = 0.6;
+/// How long ragdolls over the budget take to fade out.
+const OVER_BUDGET_FADE_OUT: Duration = Duration::from_secs(2);
+
+/// The root of a ragdoll. The skeleton is built once the model's scene is ready.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Ragdoll {
+    /// The velocity of the NPC when it died.
+    velocity: Vec3,
+    /// The velocity added by the killing blow, strongest at `impulse_point`.
+    impulse: Vec3,
+    impulse_point: Option<Vec3>,
+    /// When the ragdoll was spawned, used to fade out the oldest ones first.
+    spawned_at: Duration,
+    /// The local transforms of the named nodes of the NPC's model at the moment of death.
+    #[reflect(ignore)]
+    pose: HashMap<String, Transform>,
+}
+
+/// A rigid body or joint that belongs to a ragdoll but is not in its hierarchy.
+#[derive(Component, Deref, Debug, Reflect)]
+#[reflect(Component)]
+#[relationship(relationship_target = RagdollParts)]
+pub(crate) struct RagdollPartOf(Entity);
+
+#[derive(Component, Deref, Debug, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = RagdollPartOf, linked_spawn)]
+pub(crate) struct RagdollParts(Vec<Entity>);
+
+impl Ragdoll {
+    /// Captures the pose of the dying NPC `npc` and the impulse of the blow that killed it.
+    pub(crate) fn new(
+        npc: Entity,
+        death: &OnDeath,
+        velocity: Vec3,
+        spawned_at: Duration,
+        children: &Query<&Children>,
+        nodes: &Query<(&Name, &Transform)>,
+    ) -> Self {
+        let pose = children
+            .iter_descendants(npc)
+            .filter_map(|entity| nodes.get(entity).ok())
+            .map(|(name, transform)| (name.as_str().to_string(), *transform))
+            .collect();
+        let strength = match death.kind {
+            DamageKind::Explosion => 10.0,
+            DamageKind::Pellet => 5.0,
+            _ => 2.0,
+        };
+        Self {
+            velocity,
+            impulse: death.direction.map_or(Vec3::ZERO, |dir| dir * strength),
+            impulse_point: death.point,
+            spawned_at,
+            pose,
+        }
+    }
+}
+
+/// Spawns `ragdoll` in place of an NPC with the given transform.
+pub(crate) fn spawn_ragdoll(
+    commands: &mut Commands,
+    ragdoll: Ragdoll,
+    transform: &Transform,
+    stats: &NpcStats,
+    npc_assets: &NpcAssets,
+) {
+    commands
+        .spawn((
+            Name::new("Ragdoll"),
+            ragdoll,
+            SceneRoot(npc_assets.model.clone()),
+            // Same placement as the model of a living NPC.
+            Transform::from_translation(transform.translation - Vec3::Y * stats.float_height())
+                .with_rotation(transform.rotation)
+                .with_scale(Vec3::splat(stats.size)),
+            StateScoped(Screen::Gameplay),
+            StateScoped(LoadingScreen::Shaders),
+        ))
+        .observe(build_ragdoll);
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn build_ragdoll(
+    trigger: Trigger<SceneInstanceReady>,
+    ragdolls: Query<(&Ragdoll, &Transform)>,
+    mut transforms: Query<&mut Transform, Without<Ragdoll>>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    names: Query<&Name>,
+    skinned_meshes: Query<&SkinnedMesh>,
+    mesh_materials: Query<&MeshMaterial3d<StandardMaterial>>,
+    meshes: Query<(), With<Mesh3d>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let root = trigger.target();
+    let Ok((ragdoll, root_transform)) = ragdolls.get(root) else {
+        return;
+    };
+    // Breadth-first, so parents always come before their children.
+    let descendants = children.iter_descendants(root).collect::<Vec<_>>();
+
+    // Strike the pose the NPC died in and work out where every node is in world space.
+    let mut globals = HashMap::new();
+    globals.insert(root, GlobalTransform::from(*root_transform));
+    for &entity in &descendants {
+        let Ok(mut transform) = transforms.get_mut(entity) else {
+            continue;
+        };
+        if let Some(pose) = names
+            .get(entity)
+            .ok()
+            .and_then(|name| ragdoll.pose.get(name.as_str()))
+        {
+            *transform = *pose;
+        }
+        let Some(parent_global) = parents
+            .get(entity)
+            .ok()
+            .and_then(|child_of| globals.get(&child_of.parent()))
+        else {
+            continue;
+        };
+        globals.insert(entity, parent_global.mul_transform(*transform));
+    }
+
+    for &entity in &descendants {
+        if meshes.contains(entity) {
+            // The mesh bounds don't follow the bones, so the ragdoll could get culled while still on screen.
+            commands.entity(entity).insert(NoFrustumCulling);
+        }
+        // Fading out changes the material, which the ragdoll shares with the living NPCs.
+        if let Ok(material) = mesh_materials.get(entity) {
+            if let Some(unique) = materials.get(material.id()).cloned() {
+                commands
+                    .entity(entity)
+                    .insert(MeshMaterial3d(materials.add(unique)));
+            }
+        }
+    }
+
+    let Some(skinned_mesh) = descendants
+        .iter()
+        .find_map(|entity| skinned_meshes.get(*entity).ok())
+    else {
+        warn!("Ragdoll has no skinned mesh, leaving it as a static model");
+        return;
+    };
+    let joints = skinned_mesh.joints.iter().copied().collect::<HashSet<_>>();
+    let size = root_transform.scale.x;
+
+    // Decide which joints get a body. The topmost joint always does, so that the skeleton has something to hang from.
+    let mut bodies = HashMap::new();
+    for &joint in descendants.iter().filter(|entity| joints.contains(*entity)) {
+        let Some(global) = globals.get(&joint) else {
+            continue;
+        };
+        let child_joints = children
+            .get(joint)
+            .map(|children| {
+                children
+                    .iter()
+                    .filter(|child| joints.contains(child))
+                    .filter_map(|child| globals.get(&child))
+                    .map(|child| child.translation())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let end = (!child_joints.is_empty())
+            .then(|| child_joints.iter().sum::<Vec3>() / child_joints.len() as f32);
+        let is_root = nearest_body(joint, &parents, &bodies).is_none();
+        let long_enough =
+            end.is_some_and(|end| end.distance(global.translation()) > MIN_BONE_LENGTH * size);
+        if is_root || long_enough {
+            bodies.insert(joint, end);
+        }
+    }
+
+    for &joint in descendants
+        .iter()
+        .filter(|entity| bodies.contains_key(*entity))
+    {
+        let global = globals[&joint];
+        let (scale, rotation, translation) = global.to_scale_rotation_translation();
+        // Colliders are scaled along with the body, so they are specified in unscaled local space.
+        let radius = BONE_RADIUS * size / scale.x;
+        let collider = match bodies[&joint] {
+            Some(end) => {
+                let local_end = rotation.inverse() * (end - translation) / scale;
+                Collider::capsule_endpoints(radius, Vec3::ZERO, local_end)
+            }
+            None => Collider::sphere(radius * 2.0),
+        };
+        let falloff = ragdoll
+            .impulse_point
+            .map_or(1.0, |point| 1.0 / (1.0 + point.distance(translation)));
+        let parent_body = nearest_body(joint, &parents, &bodies);
+
+        commands.entity(joint).remove::<ChildOf>().insert((
+            Transform {
+                translation,
+                rotation,
+                scale,
+            },
+            RigidBody::Dynamic,
+            collider,
+            ColliderDensity(1_000.0),
+            LinearVelocity(ragdoll.velocity + ragdoll.impulse * falloff),
+            // Like gibs, the bones only collide with the level, not with each other or the player.
+            CollisionLayers::new(CollisionLayer::Gib, [CollisionLayer::Default]),
+            SurfaceMaterial::Flesh,
+            RagdollPartOf(root),
+        ));
+
+        if let Some(parent_body) = parent_body {
+            let parent_global = globals[&parent_body];
+            let (_, parent_rotation, parent_translation) =
+                parent_global.to_scale_rotation_translation();
+            commands.spawn((
+                Name::new("Ragdoll Joint"),
+                SphericalJoint::new(parent_body, joint)
+                    .with_local_anchor_1(
+                        parent_rotation.inverse() * (translation - parent_translation),
+                    )
+                    .with_swing_limits(-SWING_LIMIT, SWING_LIMIT)
+                    .with_twist_limits(-TWIST_LIMIT, TWIST_LIMIT),
+                RagdollPartOf(root),
+            ));
+        }
+    }
+}
+
+/// Finds the closest ancestor of `joint` that has a body.
+fn nearest_body(
+    joint: Entity,
+    parents: &Query<&ChildOf>,
+    bodies: &HashMap<Entity, Option<Vec3>>,
+) -> Option<Entity> {
+    parents
+        .iter_ancestors(joint)
+        .find(|ancestor| bodies.contains_key(ancestor))
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn enforce_ragdoll_budget(
+    ragdolls: Query<(Entity, &Ragdoll), Without<FadeOutAndDespawn>>,
+    gore_settings: Res<GoreSettings>,
+    mut commands: Commands,
+) {
+    let budget = gore_settings.ragdoll_count as usize;
+    let count = ragdolls.iter().count();
+    if count <= budget {
+        return;
+    }
+    let mut ragdolls = ragdolls.iter().collect::<Vec<_>>();
+    ragdolls.sort_by_key(|(_, ragdoll)| ragdoll.spawned_at);
+    for (entity, _) in ragdolls.into_iter().take(count - budget) {
+        commands
+            .entity(entity)
+            .insert(FadeOutAndDespawn::new(OVER_BUDGET_FADE_OUT));
+    }
+}
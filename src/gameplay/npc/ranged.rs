@@ -0,0 +1,279 @@
+//! Ranged NPCs. Instead of running up to the player, they keep their distance
+//! and spit slow projectiles that the player can dodge or shoot down.
+//! Getting hit by one slows the player down for a moment.
+//!
+//! Spitters don't have art of their own yet. They reuse the zombie model and its attack animation,
+//! and spit with the zombie shrieks in [`NpcAssets::spit_sound`]. Dedicated spit and aim animations
+//! and sounds can replace these once they exist.
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    PostPhysicsAppSystems,
+    audio::VoicePriority,
+    despawn_after::{Despawn, DespawnAfter},
+    gameplay::{
+        health::{DamageKind, Health, OnDamage, OnDeath},
+        npc::{
+            ai_state::AiState,
+            assets::NpcAssets,
+            lifecycle::{VocalOf, enemy_sound_effect},
+            stats::NpcStats,
+        },
+        player::Player,
+        status_effects::{OnStatusEffect, StatusEffectKind, StatusEffectSpec, StatusEffects},
+    },
+    screens::Screen,
+    third_party::avian3d::CollisionLayer,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Ranged, Projectile)>();
+    app.init_resource::<ProjectileAssets>();
+    app.add_systems(
+        PreUpdate,
+        update_ranged_ai_state.run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        tick_ranged_timers
+            .in_set(PostPhysicsAppSystems::TickTimers)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How far a ranged NPC backs off at once when the player gets too close.
+const RETREAT_DISTANCE: f32 = 4.0;
+const PROJECTILE_RADIUS: f32 = 0.2;
+const PROJECTILE_LIFETIME: Duration = Duration::from_secs(6);
+
+/// Marks an NPC as attacking from range instead of in melee.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Ranged {
+    /// The NPC backs off when the player is closer than this.
+    pub(crate) min_distance: f32,
+    /// The NPC moves closer when the player is further away than this.
+    pub(crate) max_distance: f32,
+    pub(crate) projectile_speed: f32,
+    pub(crate) projectile_damage: f32,
+    /// Applied to the player when a projectile hits them.
+    pub(crate) projectile_status_effect: Option<StatusEffectSpec>,
+    /// How long the NPC aims before firing. This is the player's warning.
+    pub(crate) aim_secs: f32,
+    /// The time between two shots.
+    pub(crate) cooldown: Timer,
+}
+
+impl Default for Ranged {
+    fn default() -> Self {
+        Self {
+            min_distance: 8.0,
+            max_distance: 18.0,
+            projectile_speed: 12.0,
+            projectile_damage: 12.0,
+            projectile_status_effect: Some(StatusEffectSpec::new(StatusEffectKind::Slowed, 2.0)),
+            aim_secs: 0.8,
+            cooldown: Timer::from_seconds(2.5, TimerMode::Once),
+        }
+    }
+}
+
+/// A projectile fired by a [`Ranged`] NPC.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Projectile {
+    pub(crate) damage: f32,
+    pub(crate) status_effect: Option<StatusEffectSpec>,
+    /// The NPC that fired the projectile.
+    pub(crate) instigator: Option<Entity>,
+}
+
+#[derive(Resource)]
+struct ProjectileAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for ProjectileAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Sphere::new(PROJECTILE_RADIUS).mesh().ico(2).unwrap());
+        let color = Color::srgb(0.4, 0.9, 0.1);
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: color,
+                emissive: color.to_linear() * 6.0,
+                ..default()
+            });
+        Self { mesh, material }
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_ranged_ai_state(
+    mut npcs: Query<(
+        Entity,
+        &mut AiState,
+        &mut Ranged,
+        &NpcStats,
+        &Transform,
+        Option<&StatusEffects>,
+    )>,
+    player: Single<(Entity, &Transform), With<Player>>,
+    spatial_query: SpatialQuery,
+    projectile_assets: Res<ProjectileAssets>,
+    mut npc_assets: ResMut<NpcAssets>,
+    mut commands: Commands,
+) {
+    let (player_entity, player_transform) = *player;
+    for (entity, mut ai_state, mut ranged, stats, transform, status_effects) in &mut npcs {
+        // Spit from roughly where the mouth is.
+        let origin = transform.translation + Vec3::Y * stats.half_height() * 0.6;
+        let to_player = player_transform.translation - origin;
+        let distance = to_player.length();
+        let dir = Dir3::new(to_player).ok();
+        let facing = Dir3::new(to_player.with_y(0.0)).ok();
+        let in_sight = dir.is_some_and(|dir| {
+            let filter = SpatialQueryFilter::default()
+                .with_mask([CollisionLayer::Default, CollisionLayer::Prop])
+                .with_excluded_entities([entity, player_entity]);
+            spatial_query
+                .cast_ray(origin, dir, distance, true, &filter)
+                .is_none()
+        });
+        let stunned = status_effects.is_some_and(StatusEffects::is_stunned);
+
+        match ai_state.clone() {
+            AiState::Stagger(timer) => {
+                if timer.finished() {
+                    *ai_state = AiState::Chase;
+                }
+            }
+            AiState::Aim { timer, .. } => {
+                if !in_sight || stunned {
+                    *ai_state = AiState::Chase;
+                } else if timer.finished() {
+                    let Some(dir) = dir else {
+                        continue;
+                    };
+                    commands
+                        .spawn((
+                            Name::new("Projectile"),
+                            Projectile {
+                                damage: ranged.projectile_damage,
+                                status_effect: ranged.projectile_status_effect,
+                                instigator: Some(entity),
+                            },
+                            Transform::from_translation(origin + dir * (stats.radius() + 0.3)),
+                            Mesh3d(projectile_assets.mesh.clone()),
+                            MeshMaterial3d(projectile_assets.material.clone()),
+                            RigidBody::Dynamic,
+                            Mass(1.0),
+                            GravityScale(0.0),
+                            LinearVelocity(dir * ranged.projectile_speed),
+                            Collider::sphere(PROJECTILE_RADIUS),
+                            Sensor,
+                            CollisionEventsEnabled,
+                            CollisionLayers::new(
+                                CollisionLayer::Projectile,
+                                [
+                                    CollisionLayer::Default,
+                                    CollisionLayer::Prop,
+                                    CollisionLayer::Player,
+                                ],
+                            ),
+                            // A single pellet is enough to shoot it down.
+                            Health::new(1.0),
+                            DespawnAfter::new(PROJECTILE_LIFETIME),
+                            StateScoped(Screen::Gameplay),
+                        ))
+                        .observe(hit_with_projectile)
+                        .observe(destroy_projectile);
+                    let handle = npc_assets.spit_sound.pick(&mut rand::thread_rng()).clone();
+                    commands.spawn((
+                        enemy_sound_effect(handle, *transform, stats, VoicePriority::Normal),
+                        VocalOf(entity),
+                    ));
+                    ranged.cooldown.reset();
+                    *ai_state = AiState::Reposition(transform.translation);
+                } else {
+                    *ai_state = AiState::Aim { timer, dir: facing };
+                }
+            }
+            // Ranged NPCs always know where the player is.
+            AiState::Chase
+            | AiState::Reposition(..)
+            | AiState::Attack
+            | AiState::Wander(..)
+            | AiState::Investigate(..) => {
+                let ground = transform.translation - Vec3::Y * stats.float_height();
+                *ai_state = if !in_sight || distance > ranged.max_distance {
+                    AiState::Chase
+                } else if distance < ranged.min_distance {
+                    let away = Vec3::new(-to_player.x, 0.0, -to_player.z).normalize_or_zero();
+                    AiState::Reposition(ground + away * RETREAT_DISTANCE)
+                } else if ranged.cooldown.finished() && !stunned {
+                    AiState::Aim {
+                        timer: Timer::from_seconds(ranged.aim_secs, TimerMode::Once),
+                        dir: facing,
+                    }
+                } else {
+                    // Hold the position while waiting for the next shot.
+                    AiState::Reposition(ground)
+                };
+            }
+        }
+    }
+}
+
+fn tick_ranged_timers(mut npcs: Query<(&mut AiState, &mut Ranged)>, time: Res<Time>) {
+    for (mut ai_state, mut ranged) in &mut npcs {
+        ranged.cooldown.tick(time.delta());
+        if let AiState::Aim { ref mut timer, .. } = *ai_state {
+            timer.tick(time.delta());
+        }
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn hit_with_projectile(
+    trigger: Trigger<OnCollisionStart>,
+    projectiles: Query<(&Projectile, &GlobalTransform, &LinearVelocity)>,
+    player: Query<(), With<Player>>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    let Ok((projectile, transform, velocity)) = projectiles.get(entity) else {
+        return;
+    };
+    if let Some(body) = trigger.event().body {
+        if player.contains(body) {
+            commands.entity(body).trigger(
+                OnDamage::new(projectile.damage, DamageKind::Projectile)
+                    .with_instigator(projectile.instigator)
+                    .at(transform.translation())
+                    .in_direction(Dir3::new(velocity.0).ok()),
+            );
+            if let Some(status_effect) = projectile.status_effect {
+                commands.entity(body).trigger(
+                    OnStatusEffect::new(status_effect).with_instigator(projectile.instigator),
+                );
+            }
+        }
+    }
+    // Projectiles splash on anything they touch.
+    commands.entity(entity).try_insert(Despawn);
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn destroy_projectile(trigger: Trigger<OnDeath>, mut commands: Commands) {
+    commands.entity(trigger.target()).try_insert(Despawn);
+}
@@ -1,8 +1,16 @@
-//! NPC sound handling. The only sound is a step sound that plays when the NPC is walking.
+//! NPC sound handling: footsteps while walking and a scream on death.
 
-use super::{Npc, assets::NpcAssets};
+use super::{
+    Npc,
+    assets::NpcAssets,
+    lod::{LodTier, NpcLod},
+    stats::NpcStats,
+};
 use crate::{
-    PostPhysicsAppSystems, audio::SoundEffect, gameplay::health::OnDeath, screens::Screen,
+    PostPhysicsAppSystems,
+    audio::{SoundEffect, SpatialVoice, VoicePriority},
+    gameplay::health::OnDeath,
+    screens::Screen,
 };
 use avian3d::prelude::LinearVelocity;
 use bevy::{
@@ -12,60 +20,83 @@ use bevy::{
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
 use bevy_tnua::prelude::*;
-use std::time::Duration;
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Footsteps>();
     app.add_systems(
         Update,
         play_step_sound
             .run_if(in_state(Screen::Gameplay))
             .in_set(PostPhysicsAppSystems::PlaySounds),
     );
+    app.add_observer(init_footsteps);
     app.add_observer(on_death);
 }
 
+/// The distance an NPC of size 1 covers with each step.
+const STRIDE_LENGTH: f32 = 1.5;
+/// NPCs slower than this don't make step sounds.
+const MIN_STEP_SPEED: f32 = 1.0;
+
+/// How far an NPC has walked since its last step sound.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+struct Footsteps {
+    distance: f32,
+}
+
+fn init_footsteps(trigger: Trigger<OnAdd, Npc>, mut commands: Commands) {
+    commands
+        .entity(trigger.target())
+        .insert(Footsteps::default());
+}
+
+/// Plays a step sound every time an NPC has walked a stride, so that faster NPCs step more often and larger ones less.
 #[cfg_attr(feature = "hot_patch", hot)]
 fn play_step_sound(
-    mut commands: Commands,
-    npc: Single<(Entity, &TnuaController, &LinearVelocity), With<Npc>>,
+    mut npcs: Query<(
+        Entity,
+        &mut Footsteps,
+        &TnuaController,
+        &LinearVelocity,
+        &NpcStats,
+        Option<&NpcLod>,
+    )>,
     mut npc_assets: ResMut<NpcAssets>,
     time: Res<Time>,
-    mut timer: Local<Option<Timer>>,
+    mut commands: Commands,
 ) {
-    let base_millis = 300;
-    let timer = timer.get_or_insert_with(|| {
-        Timer::new(Duration::from_millis(base_millis), TimerMode::Repeating)
-    });
-    timer.tick(time.delta());
-    if !timer.finished() {
-        return;
-    }
+    for (entity, mut footsteps, controller, linear_velocity, stats, lod) in &mut npcs {
+        let speed = linear_velocity.length();
+        if controller.is_airborne().unwrap_or(true) || speed < MIN_STEP_SPEED {
+            footsteps.distance = 0.0;
+            continue;
+        }
+        footsteps.distance += speed * time.delta_secs();
+        let stride = STRIDE_LENGTH * stats.size;
+        if footsteps.distance < stride {
+            continue;
+        }
+        footsteps.distance %= stride;
+        // Nobody hears the steps of NPCs this far away.
+        if lod.is_some_and(|lod| lod.tier == LodTier::Minimal) {
+            continue;
+        }
 
-    let (entity, controller, linear_velocity) = npc.into_inner();
-    if controller.is_airborne().unwrap_or(true) {
-        return;
-    }
-    let speed = linear_velocity.length();
-    if speed < 1.0 {
-        return;
+        let rng = &mut rand::thread_rng();
+        let sound_effect = npc_assets.steps.pick(rng).clone();
+        commands.entity(entity).with_child((
+            Transform::from_xyz(0.0, -stats.float_height(), 0.0),
+            AudioPlayer(sound_effect),
+            PlaybackSettings::DESPAWN
+                .with_spatial(true)
+                .with_speed(1.5 / stats.size.sqrt())
+                .with_volume(Volume::Linear(1.6))
+                .with_spatial_scale(SpatialScale::new(1.0 / 3.6)),
+            SoundEffect,
+            SpatialVoice(VoicePriority::Low),
+        ));
     }
-    // At speed = 5 m/s, halve the duration.
-    let speed_to_half_duration = 5.0;
-    let factor = 1.0 - (speed - speed_to_half_duration) / speed_to_half_duration;
-    timer.set_duration(Duration::from_millis((base_millis as f32 * factor) as u64));
-    let rng = &mut rand::thread_rng();
-    let sound_effect = npc_assets.steps.pick(rng).clone();
-
-    commands.entity(entity).with_child((
-        Transform::default(),
-        AudioPlayer(sound_effect),
-        PlaybackSettings::DESPAWN
-            .with_spatial(true)
-            .with_speed(1.5)
-            .with_volume(Volume::Linear(1.6))
-            .with_spatial_scale(SpatialScale::new(1.0 / 3.6)),
-        SoundEffect,
-    ));
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -98,5 +129,6 @@ fn on_death(
             .with_volume(Volume::Linear(4.0))
             .with_spatial_scale(SpatialScale::new(1.0 / 10.0)),
         SoundEffect,
+        SpatialVoice(VoicePriority::Normal),
     ));
 }
@@ -4,7 +4,7 @@ use bevy::prelude::*;
 
 use crate::gameplay::{
     health::Health,
-    npc::{NPC_CAPSULE_LENGTH, NPC_RADIUS},
+    npc::{NPC_CAPSULE_LENGTH, NPC_RADIUS, attack::AttackSet},
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -14,6 +14,7 @@ pub(super) fn plugin(app: &mut App) {
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
+#[require(AttackSet)]
 pub(crate) struct NpcStats {
     pub(crate) health: f32,
     pub(crate) desired_speed: f32,
@@ -0,0 +1,425 @@
+//! Stuck NPC recovery. An NPC that makes no progress along its navmesh path for a while
+//! goes through increasingly drastic recovery steps until it is moving again:
+//! re-pathing, a nudge, teleporting to a spot the player can't see, and finally respawning at a spawner.
+//!
+//! Every step is logged with the NPC's position so that level designers can find the spots that trap NPCs.
+//!
+//! The last enemy of a wave gets an extra fallback, since a single NPC that never reaches the player
+//! stalls the whole wave. If it doesn't get any closer to the player for a while, no matter why,
+//! it is brought to a hidden spot near the player or to a spawner, and quietly removed only if there is neither.
+//! This only applies to wave-spawned NPCs while a wave is running, not to NPCs placed in the map.
+
+use std::f32::consts::TAU;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_landmass::{AgentState, Archipelago3d, prelude::AgentTarget3d};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use rand::{Rng as _, seq::SliceRandom as _};
+
+use crate::{
+    PostPhysicsAppSystems,
+    despawn_after::Despawn,
+    gameplay::{
+        npc::{
+            Npc,
+            ai_state::AiState,
+            lifecycle::Vocal,
+            navigation::{Agent, sample_navmesh},
+            perception::Perception,
+            stats::NpcStats,
+        },
+        player::{Player, camera::PlayerCamera},
+        waves::{Spawner, Waves},
+    },
+    menus::game_over::GameOverMenu,
+    screens::Screen,
+    third_party::{avian3d::CollisionLayer, bevy_landmass::BaseArchipelago},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(StuckDetector, RecoveryStep, LastEnemy)>();
+    app.add_systems(
+        Update,
+        (recover_stuck_npcs, rescue_last_enemy)
+            .chain()
+            .in_set(PostPhysicsAppSystems::Update)
+            .run_if(|query: Query<&GameOverMenu>| query.is_empty())
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_observer(init_stuck_detector);
+}
+
+/// How long an NPC may go without progress before the next recovery step.
+const STUCK_SECS: f32 = 3.0;
+/// How much closer to its target or how far from its last checkpoint an NPC must get to count as progress.
+const PROGRESS_DISTANCE: f32 = 1.0;
+const NUDGE_SPEED: f32 = 6.0;
+/// The rings around the NPC in which teleport destinations are searched, nearest first.
+const TELEPORT_RADII: [f32; 4] = [3.0, 6.0, 10.0, 15.0];
+const TELEPORT_SAMPLES_PER_RING: usize = 8;
+/// How long the last enemy may go without getting closer to the player before it is brought to them.
+const LAST_ENEMY_SECS: f32 = 15.0;
+/// The last enemy is never rescued while it is this close to the player.
+const LAST_ENEMY_ENGAGED_DISTANCE: f32 = 4.0;
+
+/// The next thing to try when an NPC is stuck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub(crate) enum RecoveryStep {
+    /// Drop the current path so that the navmesh agent plans a fresh one.
+    #[default]
+    Repath,
+    /// Push the NPC in a random direction to free it from whatever it is caught on.
+    Nudge,
+    /// Teleport the NPC to the nearest navmesh point the player can't see.
+    Teleport,
+    /// Move the NPC to a wave spawner.
+    Respawn,
+}
+
+impl RecoveryStep {
+    fn next(self) -> Self {
+        match self {
+            RecoveryStep::Repath => RecoveryStep::Nudge,
+            RecoveryStep::Nudge => RecoveryStep::Teleport,
+            RecoveryStep::Teleport | RecoveryStep::Respawn => RecoveryStep::Respawn,
+        }
+    }
+}
+
+/// Tracks whether an NPC is making progress towards its navmesh target.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct StuckDetector {
+    /// Where the NPC was when it last made progress.
+    checkpoint: Vec3,
+    /// How far from its target the NPC was when it last made progress.
+    best_distance: f32,
+    timer: Timer,
+    next_step: RecoveryStep,
+}
+
+impl StuckDetector {
+    fn new(position: Vec3) -> Self {
+        Self {
+            checkpoint: position,
+            best_distance: f32::INFINITY,
+            timer: Timer::from_seconds(STUCK_SECS, TimerMode::Once),
+            next_step: RecoveryStep::default(),
+        }
+    }
+
+    fn reset(&mut self, position: Vec3, distance: f32) {
+        self.checkpoint = position;
+        self.best_distance = distance;
+        self.timer.reset();
+        self.next_step = RecoveryStep::default();
+    }
+}
+
+fn init_stuck_detector(
+    trigger: Trigger<OnAdd, Npc>,
+    transform: Query<&Transform>,
+    mut commands: Commands,
+) {
+    let transform = transform.get(trigger.target()).copied().unwrap_or_default();
+    commands
+        .entity(trigger.target())
+        .insert(StuckDetector::new(transform.translation));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn recover_stuck_npcs(
+    mut npcs: Query<
+        (
+            Entity,
+            NameOrEntity,
+            &mut StuckDetector,
+            &mut Transform,
+            &mut LinearVelocity,
+            &AiState,
+            &Agent,
+            &NpcStats,
+            Has<Perception>,
+        ),
+        With<Npc>,
+    >,
+    mut agents: Query<(&AgentState, &mut AgentTarget3d)>,
+    player: Single<Entity, With<Player>>,
+    player_camera: Single<&Transform, (With<PlayerCamera>, Without<Npc>)>,
+    spawners: Query<(&Transform, &Spawner), Without<Npc>>,
+    archipelago: Single<&Archipelago3d, With<BaseArchipelago>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+) {
+    let eyes = player_camera.translation;
+    for (
+        entity,
+        name,
+        mut detector,
+        mut transform,
+        mut velocity,
+        ai_state,
+        agent,
+        stats,
+        is_perceptive,
+    ) in &mut npcs
+    {
+        let Ok((agent_state, mut target)) = agents.get_mut(**agent) else {
+            continue;
+        };
+        let position = transform.translation;
+        let distance = match *target {
+            AgentTarget3d::Point(point) => position.distance(point),
+            _ => detector.best_distance,
+        };
+
+        // NPCs that are busy or where they want to be are not stuck.
+        let wants_to_move =
+            matches!(
+                ai_state,
+                AiState::Chase
+                    | AiState::Reposition(..)
+                    | AiState::Wander(..)
+                    | AiState::Investigate(..)
+            ) && !matches!(agent_state, AgentState::ReachedTarget | AgentState::Idle);
+        let made_progress = distance < detector.best_distance - PROGRESS_DISTANCE
+            || position.distance(detector.checkpoint) > PROGRESS_DISTANCE;
+        if !wants_to_move || made_progress {
+            detector.reset(position, distance);
+            continue;
+        }
+
+        detector.timer.tick(time.delta());
+        if !detector.timer.finished() {
+            continue;
+        }
+
+        let mut step = detector.next_step;
+        // NPCs placed in the level don't belong at a wave spawner, so we keep trying the other steps instead.
+        if step == RecoveryStep::Respawn && (is_perceptive || spawners.is_empty()) {
+            step = RecoveryStep::Repath;
+        }
+        match step {
+            RecoveryStep::Repath => {
+                info!("{name} is stuck at {position} ({agent_state:?}), re-pathing");
+                // The target is set again on the next navigation update, which forces a new path.
+                *target = AgentTarget3d::None;
+            }
+            RecoveryStep::Nudge => {
+                info!("{name} is still stuck at {position}, nudging it");
+                let angle = rand::thread_rng().gen_range(0.0..TAU);
+                let push = Vec3::new(angle.cos(), 0.5, angle.sin()) * NUDGE_SPEED;
+                velocity.0 += push;
+            }
+            RecoveryStep::Teleport => {
+                let feet = position - Vec3::Y * stats.float_height();
+                let destination = find_hidden_navmesh_point(
+                    feet,
+                    eyes,
+                    stats,
+                    &archipelago,
+                    &spatial_query,
+                    [entity, *player],
+                );
+                if let Some(destination) = destination {
+                    let destination = destination + Vec3::Y * stats.float_height();
+                    warn!("{name} is still stuck at {position}, teleporting it to {destination}");
+                    transform.translation = destination;
+                    velocity.0 = Vec3::ZERO;
+                } else {
+                    warn!(
+                        "{name} is still stuck at {position}, but there is no navmesh point out of sight to teleport it to"
+                    );
+                }
+            }
+            RecoveryStep::Respawn => {
+                let Some((spawner_transform, spawner)) = spawners
+                    .iter()
+                    .collect::<Vec<_>>()
+                    .choose(&mut rand::thread_rng())
+                    .copied()
+                else {
+                    continue;
+                };
+                let offset = Circle::new(spawner.radius).sample_interior(&mut rand::thread_rng());
+                let candidate = spawner_transform.translation + Vec3::new(offset.x, 0.0, offset.y);
+                let destination = sample_navmesh(&archipelago, candidate)
+                    .map_or(spawner_transform.translation, |point| {
+                        point + Vec3::Y * stats.float_height()
+                    });
+                warn!(
+                    "{name} could not be freed at {position}, respawning it at a spawner at {destination}"
+                );
+                transform.translation = destination;
+                velocity.0 = Vec3::ZERO;
+            }
+        }
+
+        let position = transform.translation;
+        detector.checkpoint = position;
+        detector.best_distance = distance;
+        detector.timer.reset();
+        detector.next_step = step.next();
+    }
+}
+
+/// Tracks whether the last remaining NPC is still coming for the player.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct LastEnemy {
+    /// The closest the NPC has been to the player since it was last rescued.
+    best_distance: f32,
+    timer: Timer,
+}
+
+impl Default for LastEnemy {
+    fn default() -> Self {
+        Self {
+            best_distance: f32::INFINITY,
+            timer: Timer::from_seconds(LAST_ENEMY_SECS, TimerMode::Once),
+        }
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn rescue_last_enemy(
+    mut npcs: Query<
+        (
+            Entity,
+            NameOrEntity,
+            &mut Transform,
+            &mut LinearVelocity,
+            &NpcStats,
+            Option<&mut LastEnemy>,
+        ),
+        // Perceptive NPCs are placed in the map rather than spawned by a wave.
+        (With<Npc>, Without<Perception>),
+    >,
+    waves: Option<Single<&Waves>>,
+    player: Single<(Entity, &Transform), (With<Player>, Without<Npc>)>,
+    player_camera: Single<&Transform, (With<PlayerCamera>, Without<Npc>, Without<Player>)>,
+    spawners: Query<(&Transform, &Spawner), Without<Npc>>,
+    archipelago: Single<&Archipelago3d, With<BaseArchipelago>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let wave_running = waves.is_some_and(|waves| !waves.is_preparing() && !waves.is_finished());
+    if !wave_running || npcs.iter().count() != 1 {
+        for (entity, _, _, _, _, last_enemy) in &npcs {
+            if last_enemy.is_some() {
+                commands.entity(entity).remove::<LastEnemy>();
+            }
+        }
+        return;
+    }
+    let Some((entity, name, mut transform, mut velocity, stats, last_enemy)) =
+        npcs.iter_mut().next()
+    else {
+        return;
+    };
+    let Some(mut last_enemy) = last_enemy else {
+        commands.entity(entity).insert(LastEnemy::default());
+        return;
+    };
+
+    let (player, player_transform) = *player;
+    let position = transform.translation;
+    let distance = position.distance(player_transform.translation);
+    if distance < LAST_ENEMY_ENGAGED_DISTANCE
+        || distance < last_enemy.best_distance - PROGRESS_DISTANCE
+    {
+        last_enemy.best_distance = distance;
+        last_enemy.timer.reset();
+        return;
+    }
+    last_enemy.timer.tick(time.delta());
+    if !last_enemy.timer.finished() {
+        return;
+    }
+    *last_enemy = LastEnemy::default();
+
+    let near_player = find_hidden_navmesh_point(
+        player_transform.translation,
+        player_camera.translation,
+        stats,
+        &archipelago,
+        &spatial_query,
+        [entity, player],
+    );
+    let at_spawner = || {
+        let (spawner_transform, _) = spawners
+            .iter()
+            .collect::<Vec<_>>()
+            .choose(&mut rand::thread_rng())
+            .copied()?;
+        sample_navmesh(&archipelago, spawner_transform.translation)
+    };
+    if let Some(destination) = near_player.or_else(at_spawner) {
+        let destination = destination + Vec3::Y * stats.float_height();
+        warn!(
+            "{name} is the last enemy and has not come for the player from {position}, moving it to {destination}"
+        );
+        transform.translation = destination;
+        velocity.0 = Vec3::ZERO;
+    } else {
+        // Better to lose an enemy than to never finish the wave. Removing it without a death
+        // makes sure that it doesn't explode or drop anything, since the player did nothing to earn that.
+        error!(
+            "{name} is the last enemy and has not come for the player from {position}, but there is nowhere to move it. Removing it."
+        );
+        commands.entity(entity).insert(Despawn);
+        commands.entity(entity).queue_handled(
+            |mut entity: EntityWorldMut| {
+                entity.despawn_related::<Vocal>();
+            },
+            bevy::ecs::error::ignore,
+        );
+    }
+}
+
+/// Looks for the navmesh point closest to `feet` that can't be seen from `eyes`.
+fn find_hidden_navmesh_point(
+    feet: Vec3,
+    eyes: Vec3,
+    stats: &NpcStats,
+    archipelago: &Archipelago3d,
+    spatial_query: &SpatialQuery,
+    excluded: [Entity; 2],
+) -> Option<Vec3> {
+    let filter = SpatialQueryFilter::default()
+        .with_mask([CollisionLayer::Default, CollisionLayer::Prop])
+        .with_excluded_entities(excluded);
+    let rng = &mut rand::thread_rng();
+    for radius in TELEPORT_RADII {
+        let start_angle = rng.gen_range(0.0..TAU);
+        let mut candidates = (0..TELEPORT_SAMPLES_PER_RING)
+            .filter_map(|i| {
+                let angle = start_angle + i as f32 / TELEPORT_SAMPLES_PER_RING as f32 * TAU;
+                let candidate = feet + Vec3::new(angle.cos(), 0.0, angle.sin()) * radius;
+                sample_navmesh(archipelago, candidate)
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| {
+            a.distance_squared(feet)
+                .total_cmp(&b.distance_squared(feet))
+        });
+        let hidden = candidates.into_iter().find(|point| {
+            // The NPC counts as visible if the player can see its head.
+            let head = *point + Vec3::Y * stats.height();
+            let to_head = head - eyes;
+            let Ok(dir) = Dir3::new(to_head) else {
+                return false;
+            };
+            spatial_query
+                .cast_ray(eyes, dir, to_head.length(), true, &filter)
+                .is_some()
+        });
+        if hidden.is_some() {
+            return hidden;
+        }
+    }
+    None
+}
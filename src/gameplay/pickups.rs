@@ -0,0 +1,345 @@
+//! Pickups that restore health, armor or ammo when the player walks over them.
+//! They are dropped by enemies according to their [`DropTable`],
+//! or placed in the level as [`HealthStation`]s and [`AmmoStation`]s that respawn their pickup on a timer.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use bevy_trenchbroom::prelude::*;
+use rand::Rng as _;
+
+use crate::{
+    PostPhysicsAppSystems,
+    auto_timer::{AutoTimer, OnAutoTimerFinish},
+    despawn_after::DespawnAfter,
+    gameplay::{
+        health::{Armor, Health, OnDeath},
+        player::{Player, gunplay::Ammo},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Pickup, PickupKind, DropTable, HealthStation, AmmoStation)>();
+    app.register_type::<(StationPickupOf, StationPickup)>();
+    app.init_resource::<PickupAssets>();
+    app.add_observer(drop_pickups);
+    app.add_observer(setup_station);
+    app.add_observer(respawn_station_pickup);
+    app.add_systems(
+        Update,
+        (attract_pickups, collect_pickups)
+            .chain()
+            .in_set(PostPhysicsAppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How close the player needs to be to collect a pickup.
+const COLLECT_DISTANCE: f32 = 1.2;
+/// How long dropped pickups stay around before disappearing.
+const DROPPED_PICKUP_LIFETIME: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub(crate) enum PickupKind {
+    Health(f32),
+    Armor(f32),
+    /// A number of shells.
+    Ammo(u32),
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Pickup {
+    pub(crate) kind: PickupKind,
+    /// Pickups fly towards the player when they are within this radius.
+    pub(crate) magnet_radius: f32,
+}
+
+impl Pickup {
+    pub(crate) fn new(kind: PickupKind) -> Self {
+        Self {
+            kind,
+            magnet_radius: 5.0,
+        }
+    }
+}
+
+/// The pickups an enemy can drop on death, each with a chance between 0 and 1.
+/// At most one pickup is dropped.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) struct DropTable(pub(crate) Vec<(PickupKind, f32)>);
+
+impl DropTable {
+    fn roll(&self, rng: &mut impl rand::Rng) -> Option<PickupKind> {
+        let mut roll = rng.gen_range(0.0..1.0);
+        for (kind, chance) in &self.0 {
+            if roll < *chance {
+                return Some(*kind);
+            }
+            roll -= chance;
+        }
+        None
+    }
+}
+
+/// A place in the level where a health pickup respawns after being collected.
+#[derive(PointClass, Component, Debug, Clone, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+pub(crate) struct HealthStation {
+    /// The amount of health restored by the pickup.
+    pub(crate) amount: f32,
+    /// The time in seconds until the pickup respawns after being collected.
+    pub(crate) respawn_secs: f32,
+}
+
+impl Default for HealthStation {
+    fn default() -> Self {
+        Self {
+            amount: 25.0,
+            respawn_secs: 30.0,
+        }
+    }
+}
+
+/// A place in the level where an ammo pickup respawns after being collected.
+#[derive(PointClass, Component, Debug, Clone, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+pub(crate) struct AmmoStation {
+    /// The number of shells restored by the pickup.
+    pub(crate) amount: u32,
+    /// The time in seconds until the pickup respawns after being collected.
+    pub(crate) respawn_secs: f32,
+}
+
+impl Default for AmmoStation {
+    fn default() -> Self {
+        Self {
+            amount: 12,
+            respawn_secs: 20.0,
+        }
+    }
+}
+
+type Stations<'w, 's> = Query<'w, 's, AnyOf<(&'static HealthStation, &'static AmmoStation)>>;
+
+/// The pickup a station hands out, and how long it takes to respawn once collected.
+fn station_pickup(stations: &Stations, station: Entity) -> Option<(PickupKind, f32)> {
+    match stations.get(station).ok()? {
+        (Some(health), _) => Some((PickupKind::Health(health.amount), health.respawn_secs)),
+        (None, Some(ammo)) => Some((PickupKind::Ammo(ammo.amount), ammo.respawn_secs)),
+        (None, None) => None,
+    }
+}
+
+#[derive(Component, Deref, Debug, Reflect)]
+#[reflect(Component)]
+#[relationship(relationship_target = StationPickup)]
+pub(crate) struct StationPickupOf(Entity);
+
+#[derive(Component, Deref, Debug, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = StationPickupOf)]
+pub(crate) struct StationPickup(Entity);
+
+#[derive(Resource)]
+struct PickupAssets {
+    mesh: Handle<Mesh>,
+    health_material: Handle<StandardMaterial>,
+    armor_material: Handle<StandardMaterial>,
+    ammo_material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for PickupAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Sphere::new(0.25).mesh().ico(2).unwrap());
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let mut glowing = |color: Color| {
+            materials.add(StandardMaterial {
+                base_color: color,
+                emissive: color.to_linear() * 8.0,
+                ..default()
+            })
+        };
+        Self {
+            mesh,
+            health_material: glowing(Color::srgb(0.9, 0.1, 0.1)),
+            armor_material: glowing(Color::srgb(0.5, 0.6, 0.7)),
+            ammo_material: glowing(Color::srgb(0.9, 0.7, 0.2)),
+        }
+    }
+}
+
+fn pickup_bundle(pickup: Pickup, assets: &PickupAssets) -> impl Bundle {
+    let material = match pickup.kind {
+        PickupKind::Health(_) => assets.health_material.clone(),
+        PickupKind::Armor(_) => assets.armor_material.clone(),
+        PickupKind::Ammo(_) => assets.ammo_material.clone(),
+    };
+    (
+        Name::new("Pickup"),
+        pickup,
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(material),
+    )
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn drop_pickups(
+    trigger: Trigger<OnDeath>,
+    drop_tables: Query<(&DropTable, &Transform)>,
+    player_armor: Query<&Armor, With<Player>>,
+    assets: Res<PickupAssets>,
+    mut commands: Commands,
+) {
+    let Ok((drop_table, transform)) = drop_tables.get(trigger.target()) else {
+        return;
+    };
+    let Some(kind) = drop_table.roll(&mut rand::thread_rng()) else {
+        return;
+    };
+    // The player starts without any armor capacity, so armor would just lie around until it disappears.
+    let can_hold_armor = player_armor.iter().any(|armor| armor.max > 0.0);
+    if matches!(kind, PickupKind::Armor(_)) && !can_hold_armor {
+        return;
+    }
+    commands.spawn((
+        pickup_bundle(Pickup::new(kind), &assets),
+        Transform::from_translation(transform.translation),
+        DespawnAfter::new(DROPPED_PICKUP_LIFETIME),
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn setup_station(
+    trigger: Trigger<OnAdd, (HealthStation, AmmoStation)>,
+    stations: Stations,
+    assets: Res<PickupAssets>,
+    mut commands: Commands,
+) {
+    spawn_station_pickup(trigger.target(), &stations, &assets, &mut commands);
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn respawn_station_pickup(
+    trigger: Trigger<OnAutoTimerFinish>,
+    stations: Stations,
+    assets: Res<PickupAssets>,
+    mut commands: Commands,
+) {
+    spawn_station_pickup(trigger.target(), &stations, &assets, &mut commands);
+}
+
+fn spawn_station_pickup(
+    station: Entity,
+    stations: &Stations,
+    assets: &PickupAssets,
+    commands: &mut Commands,
+) {
+    let Some((kind, _)) = station_pickup(stations, station) else {
+        return;
+    };
+    commands.spawn((
+        pickup_bundle(
+            // Station pickups stay in place.
+            Pickup {
+                kind,
+                magnet_radius: 0.0,
+            },
+            assets,
+        ),
+        Transform::default(),
+        ChildOf(station),
+        StationPickupOf(station),
+    ));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn attract_pickups(
+    mut pickups: Query<(&Pickup, &mut Transform), Without<ChildOf>>,
+    player: Single<&Transform, (With<Player>, Without<Pickup>)>,
+    time: Res<Time>,
+) {
+    let target = player.translation;
+    for (pickup, mut transform) in &mut pickups {
+        let distance = transform.translation.distance(target);
+        if distance > pickup.magnet_radius {
+            continue;
+        }
+        // Accelerate as the pickup gets closer.
+        let speed = 4.0 + 2.0 * (pickup.magnet_radius - distance);
+        let step = (speed * time.delta_secs()).min(distance);
+        transform.translation = transform.translation.move_towards(target, step);
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn collect_pickups(
+    pickups: Query<(Entity, &Pickup, &GlobalTransform, Option<&StationPickupOf>)>,
+    player: Single<
+        (
+            &Transform,
+            Option<&mut Health>,
+            Option<&mut Armor>,
+            Option<&mut Ammo>,
+        ),
+        With<Player>,
+    >,
+    stations: Stations,
+    mut commands: Commands,
+) {
+    let (player_transform, health, armor, ammo) = player.into_inner();
+    let (Some(mut health), Some(mut armor), Some(mut ammo)) = (health, armor, ammo) else {
+        return;
+    };
+    for (entity, pickup, transform, station_pickup_of) in &pickups {
+        if transform
+            .translation()
+            .distance(player_transform.translation)
+            > COLLECT_DISTANCE
+        {
+            continue;
+        }
+        // Leave pickups lying around if they would be wasted.
+        match pickup.kind {
+            PickupKind::Health(amount) => {
+                if health.current >= health.max {
+                    continue;
+                }
+                health.heal(amount);
+            }
+            PickupKind::Armor(amount) => {
+                if armor.current >= armor.max {
+                    continue;
+                }
+                armor.current = (armor.current + amount).min(armor.max);
+            }
+            PickupKind::Ammo(amount) => {
+                if ammo.current >= ammo.max {
+                    continue;
+                }
+                ammo.refill(amount);
+            }
+        }
+        commands.entity(entity).despawn();
+
+        if let Some(&StationPickupOf(station)) = station_pickup_of {
+            if let Some((_, respawn_secs)) = station_pickup(&stations, station) {
+                commands
+                    .entity(station)
+                    .insert(AutoTimer(Timer::from_seconds(
+                        respawn_secs,
+                        TimerMode::Once,
+                    )));
+            }
+        }
+    }
+}
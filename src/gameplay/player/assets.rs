@@ -20,14 +20,8 @@ pub(crate) struct PlayerAssets {
     #[dependency]
     pub(crate) _model: Handle<Scene>,
     #[dependency]
-    pub(crate) throw_sound: Handle<AudioSource>,
-    #[dependency]
-    pub(crate) steps: ShuffleBag<Handle<AudioSource>>,
-    #[dependency]
     pub(crate) jump_grunts: ShuffleBag<Handle<AudioSource>>,
     #[dependency]
-    pub(crate) land_sounds: ShuffleBag<Handle<AudioSource>>,
-    #[dependency]
     pub(crate) jump_start_sounds: ShuffleBag<Handle<AudioSource>>,
     #[dependency]
     pub(crate) shooting_sounds: ShuffleBag<Handle<AudioSource>>,
@@ -51,22 +45,6 @@ impl FromWorld for PlayerAssets {
         let rng = &mut rand::thread_rng();
         Self {
             _model: assets.load(Player::scene_path()),
-            throw_sound: assets.load("audio/sound_effects/throw.ogg"),
-            steps: ShuffleBag::try_new(
-                [
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_01.ogg"),
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_02.ogg"),
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_03.ogg"),
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_04.ogg"),
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_05.ogg"),
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_06.ogg"),
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_07.ogg"),
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_08.ogg"),
-                    assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_09.ogg"),
-                ],
-                rng,
-            )
-            .unwrap(),
             jump_grunts: ShuffleBag::try_new(
                 [
                     assets.load("audio/sound_effects/jump_grunt/jump_grunt_1.ogg"),
@@ -77,18 +55,6 @@ impl FromWorld for PlayerAssets {
                 rng,
             )
             .unwrap(),
-            land_sounds: ShuffleBag::try_new(
-                [
-                    assets.load("audio/sound_effects/land/Footsteps_Rock_Jump_Land_01.ogg"),
-                    assets.load("audio/sound_effects/land/Footsteps_Rock_Jump_Land_02.ogg"),
-                    assets.load("audio/sound_effects/land/Footsteps_Rock_Jump_Land_03.ogg"),
-                    assets.load("audio/sound_effects/land/Footsteps_Rock_Jump_Land_04.ogg"),
-                    assets.load("audio/sound_effects/land/Footsteps_Rock_Jump_Land_05.ogg"),
-                    assets.load("audio/sound_effects/land/Footsteps_Rock_Jump_Land_06.ogg"),
-                ],
-                rng,
-            )
-            .unwrap(),
             jump_start_sounds: ShuffleBag::try_new(
                 [
                     assets.load("audio/sound_effects/jump_start/Footsteps_Rock_Jump_Start_01.ogg"),
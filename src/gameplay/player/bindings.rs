@@ -0,0 +1,391 @@
+//! The player's rebindable controls.
+//!
+//! Every control has one keyboard or mouse input and optionally one gamepad button.
+//! The bindings are saved so that they survive restarting the game:
+//! natively in a file next to the game's executable, and on the web in the browser's local storage.
+//!
+//! P and Escape pause the game, so they can't be bound to a control.
+
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant},
+};
+use bevy_enhanced_input::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Bindings, Control, ControlBinding, BoundInput)>();
+    app.init_resource::<Bindings>();
+    app.add_systems(Startup, load_bindings);
+}
+
+/// Something the player can do that can be bound to an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub(crate) enum Control {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sprint,
+    Crouch,
+    Slide,
+    Dash,
+    Shoot,
+    PickupProp,
+    DropProp,
+    OpenUpgradeMenu,
+}
+
+impl Control {
+    pub(crate) const ALL: [Control; 13] = [
+        Control::MoveForward,
+        Control::MoveBackward,
+        Control::MoveLeft,
+        Control::MoveRight,
+        Control::Jump,
+        Control::Sprint,
+        Control::Crouch,
+        Control::Slide,
+        Control::Dash,
+        Control::Shoot,
+        Control::PickupProp,
+        Control::DropProp,
+        Control::OpenUpgradeMenu,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Control::MoveForward => "Move Forward",
+            Control::MoveBackward => "Move Backward",
+            Control::MoveLeft => "Move Left",
+            Control::MoveRight => "Move Right",
+            Control::Jump => "Jump",
+            Control::Sprint => "Sprint",
+            Control::Crouch => "Crouch",
+            Control::Slide => "Slide",
+            Control::Dash => "Air Dash",
+            Control::Shoot => "Shoot",
+            Control::PickupProp => "Pick Up Prop",
+            Control::DropProp => "Drop Prop",
+            Control::OpenUpgradeMenu => "Open Upgrade Menu",
+        }
+    }
+
+    /// Moving on a gamepad always uses the left stick, so the directions have no gamepad buttons.
+    pub(crate) fn has_gamepad_binding(self) -> bool {
+        !matches!(
+            self,
+            Control::MoveForward | Control::MoveBackward | Control::MoveLeft | Control::MoveRight
+        )
+    }
+
+    fn default_binding(self) -> ControlBinding {
+        let (keyboard, gamepad) = match self {
+            Control::MoveForward => (BoundInput::Key(KeyCode::KeyW), None),
+            Control::MoveBackward => (BoundInput::Key(KeyCode::KeyS), None),
+            Control::MoveLeft => (BoundInput::Key(KeyCode::KeyA), None),
+            Control::MoveRight => (BoundInput::Key(KeyCode::KeyD), None),
+            Control::Jump => (BoundInput::Key(KeyCode::Space), Some(GamepadButton::South)),
+            Control::Sprint => (
+                BoundInput::Key(KeyCode::ShiftLeft),
+                Some(GamepadButton::LeftThumb),
+            ),
+            Control::Crouch => (
+                BoundInput::Key(KeyCode::ControlLeft),
+                Some(GamepadButton::RightThumb),
+            ),
+            Control::Slide => (BoundInput::Key(KeyCode::KeyC), Some(GamepadButton::West)),
+            Control::Dash => (
+                BoundInput::Key(KeyCode::KeyQ),
+                Some(GamepadButton::LeftTrigger),
+            ),
+            Control::Shoot => (
+                BoundInput::Mouse(MouseButton::Left),
+                Some(GamepadButton::RightTrigger2),
+            ),
+            Control::PickupProp => (BoundInput::Key(KeyCode::KeyE), Some(GamepadButton::East)),
+            Control::DropProp => (BoundInput::Key(KeyCode::KeyG), Some(GamepadButton::East)),
+            Control::OpenUpgradeMenu => {
+                (BoundInput::Key(KeyCode::KeyF), Some(GamepadButton::North))
+            }
+        };
+        ControlBinding { keyboard, gamepad }
+    }
+
+    fn from_debug_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|control| format!("{control:?}") == name)
+    }
+}
+
+/// The inputs bound to a single [`Control`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub(crate) struct ControlBinding {
+    /// Either a key or a mouse button.
+    pub(crate) keyboard: BoundInput,
+    pub(crate) gamepad: Option<GamepadButton>,
+}
+
+/// Keys that the game itself listens for, which can't be bound to a [`Control`].
+/// Both of them pause the game.
+const RESERVED_KEYS: [KeyCode; 2] = [KeyCode::KeyP, KeyCode::Escape];
+
+/// A single input that can be bound to a [`Control`].
+///
+/// Saved bindings use the [`Debug`] representation, e.g. `Key(Space)` or `Gamepad(South)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub(crate) enum BoundInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl BoundInput {
+    /// A short, human-readable name for the input.
+    pub(crate) fn label(self) -> String {
+        match self {
+            BoundInput::Key(key) => {
+                let name = format!("{key:?}");
+                name.strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name)
+                    .to_string()
+            }
+            BoundInput::Mouse(button) => format!("{button:?} Mouse"),
+            BoundInput::Gamepad(button) => format!("{button:?}"),
+        }
+    }
+
+    /// Inputs without a name of their own, like unidentified keys, can't be saved.
+    pub(crate) fn is_bindable(self) -> bool {
+        !matches!(
+            self,
+            BoundInput::Key(KeyCode::Unidentified(_))
+                | BoundInput::Mouse(MouseButton::Other(_))
+                | BoundInput::Gamepad(GamepadButton::Other(_))
+        )
+    }
+
+    /// Whether the game itself uses the input, see [`RESERVED_KEYS`].
+    pub(crate) fn is_reserved(self) -> bool {
+        matches!(self, BoundInput::Key(key) if RESERVED_KEYS.contains(&key))
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let (kind, variant) = text.strip_suffix(')')?.split_once('(')?;
+        match kind {
+            "Key" => unit_variant(variant).map(BoundInput::Key),
+            "Mouse" => unit_variant(variant).map(BoundInput::Mouse),
+            "Gamepad" => unit_variant(variant).map(BoundInput::Gamepad),
+            _ => None,
+        }
+    }
+}
+
+impl From<BoundInput> for Input {
+    fn from(input: BoundInput) -> Self {
+        match input {
+            BoundInput::Key(key) => key.into(),
+            BoundInput::Mouse(button) => button.into(),
+            BoundInput::Gamepad(button) => button.into(),
+        }
+    }
+}
+
+/// Looks up a fieldless enum variant like [`KeyCode::Space`] by its name.
+fn unit_variant<T: FromReflect>(name: &str) -> Option<T> {
+    T::from_reflect(&DynamicEnum::new(name.to_string(), DynamicVariant::Unit))
+}
+
+/// An input that [`Bindings::bind_keyboard`] refused because the game itself uses it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ReservedInput(pub(crate) BoundInput);
+
+/// The inputs bound to every [`Control`]. Controls without an entry use their default binding.
+#[derive(Resource, Reflect, Debug, Clone, Default, PartialEq)]
+#[reflect(Resource)]
+pub(crate) struct Bindings(HashMap<Control, ControlBinding>);
+
+impl Bindings {
+    pub(crate) fn get(&self, control: Control) -> ControlBinding {
+        self.0
+            .get(&control)
+            .copied()
+            .unwrap_or_else(|| control.default_binding())
+    }
+
+    fn get_mut(&mut self, control: Control) -> &mut ControlBinding {
+        self.0
+            .entry(control)
+            .or_insert_with(|| control.default_binding())
+    }
+
+    /// Binds a key or mouse button to `control`.
+    ///
+    /// If another control already used that input, the two controls swap their inputs.
+    /// Returns the other control in that case.
+    /// Reserved keys are refused without changing any bindings.
+    pub(crate) fn bind_keyboard(
+        &mut self,
+        control: Control,
+        input: BoundInput,
+    ) -> Result<Option<Control>, ReservedInput> {
+        if input.is_reserved() {
+            return Err(ReservedInput(input));
+        }
+        let previous = self.get(control).keyboard;
+        let conflict = Control::ALL
+            .into_iter()
+            .find(|other| *other != control && self.get(*other).keyboard == input);
+        if let Some(other) = conflict {
+            self.get_mut(other).keyboard = previous;
+        }
+        self.get_mut(control).keyboard = input;
+        Ok(conflict)
+    }
+
+    /// Binds a gamepad button to `control`, swapping with any other control that used it,
+    /// just like [`Bindings::bind_keyboard`].
+    pub(crate) fn bind_gamepad(
+        &mut self,
+        control: Control,
+        button: GamepadButton,
+    ) -> Option<Control> {
+        let previous = self.get(control).gamepad;
+        let conflict = Control::ALL.into_iter().find(|other| {
+            *other != control
+                && other.has_gamepad_binding()
+                && self.get(*other).gamepad == Some(button)
+        });
+        if let Some(other) = conflict {
+            self.get_mut(other).gamepad = previous;
+        }
+        self.get_mut(control).gamepad = Some(button);
+        conflict
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.0.clear();
+    }
+
+    pub(crate) fn save(&self) {
+        let mut text = "# Delete this file to restore the default bindings.\n".to_string();
+        for control in Control::ALL {
+            let binding = self.get(control);
+            text += &format!("{control:?} = {:?}", binding.keyboard);
+            if let Some(button) = binding.gamepad {
+                text += &format!(", {:?}", BoundInput::Gamepad(button));
+            }
+            text += "\n";
+        }
+        if let Err(err) = storage::write(&text) {
+            warn!("Could not save bindings: {err}");
+        }
+    }
+
+    /// Reads bindings saved by [`Bindings::save`]. Lines that can't be read are skipped.
+    fn parse(text: &str) -> Self {
+        let mut bindings = Self::default();
+        let lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for line in lines {
+            let Some((control, inputs)) = line.split_once('=') else {
+                warn!("Skipping malformed binding: {line}");
+                continue;
+            };
+            let Some(control) = Control::from_debug_name(control.trim()) else {
+                warn!("Skipping binding for unknown control: {line}");
+                continue;
+            };
+            // A saved control without a gamepad button was deliberately left unbound.
+            let binding = bindings.get_mut(control);
+            binding.gamepad = None;
+            for input in inputs.split(',').map(str::trim) {
+                match BoundInput::parse(input) {
+                    Some(BoundInput::Gamepad(button)) if control.has_gamepad_binding() => {
+                        binding.gamepad = Some(button);
+                    }
+                    Some(BoundInput::Gamepad(_)) | None => {
+                        warn!("Skipping unknown input {input} for {control:?}");
+                    }
+                    Some(input) if input.is_reserved() => {
+                        warn!("Skipping reserved input {input:?} for {control:?}");
+                    }
+                    Some(input) => binding.keyboard = input,
+                }
+            }
+        }
+        bindings
+    }
+}
+
+fn load_bindings(mut bindings: ResMut<Bindings>) {
+    match storage::read() {
+        Ok(Some(text)) => *bindings = Bindings::parse(&text),
+        Ok(None) => {}
+        Err(err) => warn!("Could not load bindings, using the defaults: {err}"),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod storage {
+    use std::{env, fs, io::ErrorKind, path::PathBuf};
+
+    use anyhow::anyhow;
+
+    const BINDINGS_FILE: &str = "bindings.cfg";
+
+    /// The bindings file lives next to the executable, so that it doesn't depend on where the game was started from.
+    fn bindings_path() -> anyhow::Result<PathBuf> {
+        let exe = env::current_exe()?;
+        let dir = exe
+            .parent()
+            .ok_or_else(|| anyhow!("The executable has no parent directory"))?;
+        Ok(dir.join(BINDINGS_FILE))
+    }
+
+    pub(super) fn read() -> anyhow::Result<Option<String>> {
+        match fs::read_to_string(bindings_path()?) {
+            Ok(text) => Ok(Some(text)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub(super) fn write(text: &str) -> anyhow::Result<()> {
+        fs::write(bindings_path()?, text)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod storage {
+    use anyhow::anyhow;
+    use web_sys::Storage;
+
+    const BINDINGS_KEY: &str = "chainboom_bindings";
+
+    fn local_storage() -> anyhow::Result<Storage> {
+        web_sys::window()
+            .ok_or_else(|| anyhow!("No window"))?
+            .local_storage()
+            .map_err(|err| anyhow!("{err:?}"))?
+            .ok_or_else(|| anyhow!("Local storage is not available"))
+    }
+
+    pub(super) fn read() -> anyhow::Result<Option<String>> {
+        local_storage()?
+            .get_item(BINDINGS_KEY)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    pub(super) fn write(text: &str) -> anyhow::Result<()> {
+        local_storage()?
+            .set_item(BINDINGS_KEY, text)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+}
@@ -9,7 +9,10 @@ use bevy::{
     core_pipeline::{Skybox, bloom::Bloom, tonemapping::Tonemapping},
     pbr::NotShadowCaster,
     prelude::*,
-    render::{camera::Exposure, view::RenderLayers},
+    render::{
+        camera::Exposure,
+        view::{ColorGrading, RenderLayers},
+    },
     scene::SceneInstanceReady,
     window::CursorGrabMode,
 };
@@ -73,7 +76,7 @@ pub(crate) struct PlayerCamera;
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
 #[require(Transform, Visibility)]
-struct WorldModelCamera;
+pub(crate) struct WorldModelCamera;
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn spawn_view_model(
@@ -124,6 +127,8 @@ fn spawn_view_model(
                 ),
                 Exposure::INDOOR,
                 Tonemapping::TonyMcMapface,
+                // Adjusted at low health, see `damage_feedback`.
+                ColorGrading::default(),
                 Bloom::NATURAL,
                 Skybox {
                     image: level_assets.env_map_specular.clone(),
@@ -7,7 +7,10 @@ use bevy_enhanced_input::prelude::*;
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
 
-use super::Player;
+use super::{
+    Player,
+    bindings::{Bindings, Control},
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Record directional input as movement controls.
@@ -19,7 +22,8 @@ pub(super) fn plugin(app: &mut App) {
     app.register_type::<BlocksInput>();
     app.add_systems(
         PreUpdate,
-        update_player_input_binding.run_if(resource_changed::<BlocksInput>),
+        update_player_input_binding
+            .run_if(resource_changed::<BlocksInput>.or(resource_changed::<Bindings>)),
     );
 }
 
@@ -34,6 +38,22 @@ pub(crate) struct Move;
 #[input_action(output = bool)]
 pub(crate) struct Jump;
 
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+pub(crate) struct Sprint;
+
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+pub(crate) struct Crouch;
+
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+pub(crate) struct Slide;
+
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+pub(crate) struct Dash;
+
 #[derive(Debug, InputAction)]
 #[input_action(output = Vec2)]
 pub(crate) struct Rotate;
@@ -61,6 +81,7 @@ pub(crate) struct DefaultInputContext;
 fn default_binding(
     trigger: Trigger<Binding<DefaultInputContext>>,
     mut players: Query<&mut Actions<DefaultInputContext>>,
+    bindings: Res<Bindings>,
 ) {
     const DEFAULT_SPEED: f32 = 8.0;
     let mut actions = players.get_mut(trigger.target()).unwrap();
@@ -71,7 +92,15 @@ fn default_binding(
     // be triggered with any non-zero value.
     actions
         .bind::<Move>()
-        .to((Cardinal::wasd_keys(), Axial::left_stick()))
+        .to((
+            Cardinal {
+                north: Input::from(bindings.get(Control::MoveForward).keyboard),
+                east: Input::from(bindings.get(Control::MoveRight).keyboard),
+                south: Input::from(bindings.get(Control::MoveBackward).keyboard),
+                west: Input::from(bindings.get(Control::MoveLeft).keyboard),
+            },
+            Axial::left_stick(),
+        ))
         .with_modifiers((
             DeadZone::default(), // Apply non-uniform normalization to ensure consistent speed, otherwise diagonal movement will be faster.
             SmoothNudge::default(), // Make movement smooth and independent of the framerate. To only make it framerate-independent, use `DeltaScale`.
@@ -82,9 +111,11 @@ fn default_binding(
 
     // Multiple inputs can be assigned to a single action,
     // and the action will respond to any of them.
-    actions
-        .bind::<Jump>()
-        .to((KeyCode::Space, GamepadButton::South));
+    bind_control::<Jump>(&mut actions, &bindings, Control::Jump);
+    bind_control::<Sprint>(&mut actions, &bindings, Control::Sprint);
+    bind_control::<Crouch>(&mut actions, &bindings, Control::Crouch);
+    bind_control::<Slide>(&mut actions, &bindings, Control::Slide);
+    bind_control::<Dash>(&mut actions, &bindings, Control::Dash);
 
     // actions
     //     .bind::<Interact>()
@@ -96,17 +127,24 @@ fn default_binding(
         .to((Input::mouse_motion(), Axial::right_stick()))
         .with_modifiers((Negate::all(), Scale::splat(DEFAULT_SENSITIVITY)));
 
-    actions
-        .bind::<PickupProp>()
-        .to((KeyCode::KeyE, GamepadButton::East));
-
-    actions
-        .bind::<DropProp>()
-        .to((KeyCode::KeyG, GamepadButton::East));
-
-    actions.bind::<Shoot>().to(MouseButton::Left);
+    bind_control::<PickupProp>(&mut actions, &bindings, Control::PickupProp);
+    bind_control::<DropProp>(&mut actions, &bindings, Control::DropProp);
+    bind_control::<Shoot>(&mut actions, &bindings, Control::Shoot);
+    bind_control::<OpenUpgradeMenu>(&mut actions, &bindings, Control::OpenUpgradeMenu);
+}
 
-    actions.bind::<OpenUpgradeMenu>().to(KeyCode::KeyF);
+/// Binds the action to the inputs the player chose for `control`.
+fn bind_control<A: InputAction>(
+    actions: &mut Actions<DefaultInputContext>,
+    bindings: &Bindings,
+    control: Control,
+) {
+    let binding = bindings.get(control);
+    let action = actions.bind::<A>();
+    action.to(Input::from(binding.keyboard));
+    if let Some(button) = binding.gamepad {
+        action.to(button);
+    }
 }
 
 #[derive(Resource, Default, Reflect, Deref, DerefMut)]
@@ -119,8 +157,10 @@ fn update_player_input_binding(
     mut commands: Commands,
 ) {
     if blocks_input.is_empty() {
+        // Re-adding the actions triggers the `Binding` observer, which picks up changed bindings.
         commands
             .entity(*player)
+            .remove::<Actions<DefaultInputContext>>()
             .insert(Actions::<DefaultInputContext>::default());
     } else {
         commands
@@ -3,13 +3,22 @@ use std::time::Duration;
 use super::{Player, assets::PlayerAssets, camera::PlayerCamera, default_input::Shoot};
 use crate::{
     RenderLayer,
-    audio::{sound_effect, sped_up_sound_effect},
+    audio::sound_effect,
     despawn_after::DespawnAfter,
     gameplay::{
         crosshair::CrosshairState,
-        health::OnDamage,
-        npc::Npc,
+        explosion::assets::ExplosionAssets,
+        gore_settings::GoreSettings,
+        health::{DamageKind, DamageSource, OnDamage, WeaponId},
+        npc::{
+            Npc,
+            dismemberment::{Crawler, hit_zone},
+            stats::NpcStats,
+        },
         player::{GroundCast, camera::CustomRenderLayer, camera_shake::OnTrauma},
+        status_effects::{OnStatusEffect, StatusEffectSpec, StatusEffects},
+        surface::{SurfaceAssets, SurfaceEvent, Surfaces, bullet_decal_transform},
+        waves::WaveAdvanced,
     },
     screens::Screen,
     third_party::avian3d::CollisionLayer,
@@ -18,9 +27,13 @@ use avian3d::prelude::*;
 use bevy::{prelude::*, render::view::RenderLayers, window::CursorGrabMode};
 use bevy_enhanced_input::prelude::*;
 use bevy_hanabi::prelude::*;
+use bevy_mesh_decal::spray_decal;
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
 
+/// The [`WeaponId`] of the shotgun, which is currently the only weapon.
+pub(crate) const SHOTGUN: WeaponId = WeaponId(0);
+
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
 pub(crate) struct Shooting;
@@ -29,7 +42,7 @@ pub(crate) struct Shooting;
 #[reflect(Component)]
 pub(crate) struct Reloading;
 
-#[derive(Debug, Component, Reflect)]
+#[derive(Debug, Component, Clone, Reflect)]
 #[reflect(Component)]
 pub(crate) struct WeaponStats {
     pub(crate) damage: f32,
@@ -37,11 +50,45 @@ pub(crate) struct WeaponStats {
     pub(crate) spread_radius: f32,
     pub(crate) pushback: f32,
     pub(crate) extra_enemy_explosion_radius: f32,
+    /// Scales the knockback from explosions the player set off themselves.
+    pub(crate) blast_jump: f32,
+    /// Scales the damage from explosions the player set off themselves.
+    pub(crate) self_blast_damage: f32,
+    /// A status effect applied to everything hit by a pellet.
+    pub(crate) status_effect: Option<StatusEffectSpec>,
+}
+
+/// The shells the player has left. Every shot uses up one.
+#[derive(Debug, Component, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Ammo {
+    pub(crate) current: u32,
+    pub(crate) max: u32,
+}
+
+impl Ammo {
+    pub(crate) fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub(crate) fn refill(&mut self, amount: u32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
+    pub(crate) fn refill_full(&mut self) {
+        self.current = self.max;
+    }
 }
 
+/// How many shells the player can carry.
+const MAX_AMMO: u32 = 48;
+
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Ammo>();
+
     // Only until there is a weapon pickup/selection system
     app.add_observer(setup_weapon_stats);
+    app.add_observer(resupply_between_waves);
 
     app.add_observer(shooting);
     app.add_observer(shooting_sounds);
@@ -72,13 +119,27 @@ fn setup_weapon_stats(trigger: Trigger<OnAdd, Player>, mut commands: Commands) {
         spread_radius: 0.15,
         pushback: 12.0,
         extra_enemy_explosion_radius: 0.0,
+        blast_jump: 1.0,
+        self_blast_damage: 1.0,
+        status_effect: None,
     });
+    commands
+        .entity(trigger.target())
+        .insert(Ammo::new(MAX_AMMO));
+}
+
+/// Refills the player's shells at the start of every wave,
+/// so that running dry never leaves them without a way to fight back.
+fn resupply_between_waves(_trigger: Trigger<WaveAdvanced>, mut ammo: Single<&mut Ammo>) {
+    ammo.refill_full();
 }
 
 fn shooting(
     trigger: Trigger<Fired<Shoot>>,
     mut commands: Commands,
     shooting: Query<(), With<Shooting>>,
+    status_effects: Query<&StatusEffects>,
+    mut ammo: Query<&mut Ammo>,
     crosshair_state: Single<&CrosshairState>,
 ) {
     let entity = trigger.target();
@@ -86,6 +147,18 @@ fn shooting(
     if shooting.contains(entity) || !crosshair_state.wants_invisible.is_empty() {
         return;
     }
+    if status_effects
+        .get(entity)
+        .is_ok_and(StatusEffects::is_stunned)
+    {
+        return;
+    }
+    if let Ok(mut ammo) = ammo.get_mut(entity) {
+        if ammo.current == 0 {
+            return;
+        }
+        ammo.current -= 1;
+    }
 
     commands.entity(entity).insert(Shooting);
     commands.trigger(OnTrauma(0.4));
@@ -220,11 +293,17 @@ fn handle_hits(
     player: Single<Entity, With<Player>>,
     bullet_impact: Res<BulletImpact>,
     mut commands: Commands,
-    npcs: Query<(), With<Npc>>,
-    mut player_assets: ResMut<PlayerAssets>,
+    npcs: Query<(&Transform, &NpcStats, Has<Crawler>), With<Npc>>,
     state: Res<State<Screen>>,
+    mut surfaces: Surfaces,
+    mut surface_assets: ResMut<SurfaceAssets>,
+    mut explosion_assets: ResMut<ExplosionAssets>,
+    gore_settings: Res<GoreSettings>,
 ) {
+    /// Decals are expensive to build, so only the first few pellets of a shot leave one.
+    const DECALS_PER_SHOT: usize = 4;
     let mut rng = &mut rand::thread_rng();
+    let mut decals = 0;
 
     // Ray origin and base direction
     let origin = player_camera_parent.translation;
@@ -249,6 +328,7 @@ fn handle_hits(
                 CollisionLayer::Npc,
                 CollisionLayer::Prop,
                 CollisionLayer::Default,
+                CollisionLayer::Projectile,
             ])
             .with_excluded_entities([*player]);
 
@@ -268,18 +348,18 @@ fn handle_hits(
             ),
         ));
 
-        if npcs.contains(first_hit.entity) {
-            // play jump sound sped up, sound like flesh impact
-            let rng = &mut rand::thread_rng();
-            let sound = player_assets.jump_start_sounds.pick(rng).clone();
-            if *state == Screen::Gameplay {
-                commands.spawn(sped_up_sound_effect(sound.clone()));
-            }
-        } else {
-            // play throw sound sped up, sounds like wall impact
-            let sound = player_assets.throw_sound.clone();
-            if *state == Screen::Gameplay {
-                commands.spawn(sped_up_sound_effect(sound.clone()));
+        let hit_point = origin + spread_direction * first_hit.distance;
+        let surface = surfaces.surface_of(first_hit.entity, Ray3d::new(origin, spread_direction));
+        if *state == Screen::Gameplay {
+            commands.spawn(surface.sound(SurfaceEvent::BulletImpact, &mut surface_assets, rng));
+        }
+        if decals < DECALS_PER_SHOT {
+            if let Some(decal) =
+                surface_assets.bullet_decal(surface, &mut explosion_assets, &gore_settings, rng)
+            {
+                let transform = bullet_decal_transform(surface, hit_point, first_hit.normal, rng);
+                spray_decal(&mut commands, decal, transform);
+                decals += 1;
             }
         }
 
@@ -288,9 +368,21 @@ fn handle_hits(
             continue;
         };
 
-        commands
-            .entity(*body)
-            .trigger(OnDamage(weapon_stats.damage));
+        let zone_multiplier = npcs.get(*body).map_or(1.0, |(transform, stats, crawling)| {
+            hit_zone(transform, stats, crawling, hit_point).damage_multiplier()
+        });
+        commands.entity(*body).trigger(
+            OnDamage::new(weapon_stats.damage * zone_multiplier, DamageKind::Pellet)
+                .with_instigator(*player)
+                .at(hit_point)
+                .in_direction(spread_direction)
+                .with_source(DamageSource::Weapon(SHOTGUN)),
+        );
+        if let Some(status_effect) = weapon_stats.status_effect {
+            commands
+                .entity(*body)
+                .trigger(OnStatusEffect::new(status_effect).with_instigator(*player));
+        }
     }
 }
 
@@ -3,7 +3,7 @@ use bevy::prelude::*;
 use crate::{
     audio::sound_effect,
     gameplay::{
-        health::{Health, OnDamage},
+        health::{Health, OnDamageTaken},
         player::{Player, assets::PlayerAssets, camera_shake::OnTrauma},
     },
 };
@@ -13,7 +13,7 @@ pub(super) fn plugin(app: &mut App) {
 }
 
 fn shake_on_hit(
-    trigger: Trigger<OnDamage>,
+    trigger: Trigger<OnDamageTaken>,
     player: Query<&Health, With<Player>>,
     mut commands: Commands,
     mut player_assets: ResMut<PlayerAssets>,
@@ -23,7 +23,8 @@ fn shake_on_hit(
     };
 
     let base_trauma = 0.7 / 10.0;
-    let dmg = trigger.event().0;
+    // Shake by the health actually lost, so that resistances, shields and armor soften the hit.
+    let dmg = trigger.event().health_lost;
     commands.trigger(OnTrauma(base_trauma * dmg));
 
     if !health.is_dead() {
@@ -15,12 +15,20 @@ use bevy_trenchbroom::prelude::*;
 use default_input::DefaultInputContext;
 use navmesh_position::LastValidPlayerNavmeshPosition;
 
-use crate::{gameplay::player::movement::MovementStats, third_party::avian3d::CollisionLayer};
+use crate::{
+    gameplay::{
+        explosion::EXPLOSION_PLAYER_DAMAGE_SCALE,
+        player::movement::{MovementState, MovementStats, Stamina},
+        surface::SurfaceMaterial,
+    },
+    third_party::avian3d::CollisionLayer,
+};
 
-use super::health::Health;
+use super::health::{Armor, DamageKind, DamageMultipliers, Health, Regeneration, Shield};
 
 mod animation;
 pub(crate) mod assets;
+pub(crate) mod bindings;
 pub(crate) mod camera;
 pub(crate) mod camera_shake;
 pub(crate) mod default_input;
@@ -38,6 +46,7 @@ pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         animation::plugin,
         assets::plugin,
+        bindings::plugin,
         camera::plugin,
         default_input::plugin,
         fall_damage::plugin,
@@ -57,10 +66,14 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(QuakeClass, Component)]
 #[base(Transform, Visibility)]
 #[model("models/guns/pump_action_shotgun.gltf")]
+#[require(SurfaceMaterial = SurfaceMaterial::Flesh)]
 // In Wasm, TrenchBroom classes are not automatically registered.
 // So, we need to manually register the class in `src/third_party/bevy_trenchbroom/mod.rs`.
 pub(crate) struct Player;
 
+/// The health the player starts with, and respawns with.
+pub(crate) const PLAYER_MAX_HEALTH: f32 = 100.0;
+
 /// The radius of the player character's capsule.
 pub(crate) const PLAYER_RADIUS: f32 = 0.5;
 /// The length of the player character's capsule. Note that
@@ -90,6 +103,8 @@ fn setup_player(trigger: Trigger<OnAdd, Player>, mut commands: Commands) {
             // engine.
             Collider::capsule(PLAYER_RADIUS, PLAYER_CAPSULE_LENGTH),
             MovementStats::default(),
+            MovementState::default(),
+            Stamina::default(),
             // This is Tnua's interface component.
             TnuaController::default(),
             // A sensor shape is not strictly necessary, but without it we'll get weird results.
@@ -110,7 +125,12 @@ fn setup_player(trigger: Trigger<OnAdd, Player>, mut commands: Commands) {
                 [CollisionLayer::Character, CollisionLayer::Player],
                 LayerMask::ALL,
             ),
-            Health::new(100.0),
+            Health::new(PLAYER_MAX_HEALTH),
+            // Armor and regeneration start out empty and are unlocked through upgrades.
+            Armor::new(0.0),
+            Shield::new(25.0),
+            Regeneration::default(),
+            DamageMultipliers::default().with(DamageKind::Explosion, EXPLOSION_PLAYER_DAMAGE_SCALE),
             TnuaAnimatingState::<PlayerAnimationState>::default(),
             children![(
                 Name::new("Player Landmass Character"),
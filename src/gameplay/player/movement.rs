@@ -1,16 +1,20 @@
 use std::f32::consts::TAU;
 
+use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_enhanced_input::prelude::*;
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
-use bevy_tnua::prelude::*;
+use bevy_tnua::{builtins::TnuaBuiltinKnockback, prelude::*};
 
-use crate::fixed_update_inspection::did_fixed_update_happen;
+use crate::{
+    fixed_update_inspection::did_fixed_update_happen, gameplay::status_effects::StatusEffects,
+    third_party::avian3d::CollisionLayer,
+};
 
-use super::default_input::{Jump, Move};
+use super::default_input::{Crouch, Dash, Jump, Move, Slide, Sprint};
 
-use super::PLAYER_FLOAT_HEIGHT;
+use super::{PLAYER_CAPSULE_LENGTH, PLAYER_FLOAT_HEIGHT, PLAYER_RADIUS};
 use super::{Player, camera::PlayerCamera};
 
 pub(super) fn plugin(app: &mut App) {
@@ -23,27 +27,163 @@ pub(super) fn plugin(app: &mut App) {
         clear_accumulated_input.run_if(did_fixed_update_happen),
     );
     app.add_observer(jump);
+    app.add_observer(knockback);
     app.add_observer(accumulate_movement);
+    app.add_observer(accumulate_sprint);
+    app.add_observer(accumulate_crouch);
+    app.add_observer(request_slide);
+    app.add_observer(request_dash);
     app.add_observer(init_accumulated_input);
 
-    app.register_type::<AccumulatedInput>();
+    app.register_type::<(AccumulatedInput, MovementStats, MovementState, Stamina)>();
 }
 
+/// How much lower the player's center is while crouching. The capsule shrinks by the same amount.
+const CROUCH_HEIGHT_OFFSET: f32 = 0.5;
+/// Slides only start above this horizontal speed, and end once they slow down below it.
+const MIN_SLIDE_SPEED: f32 = 6.0;
+/// How quickly a slide loses speed, in m/s².
+const SLIDE_DECELERATION: f32 = 8.0;
+/// How long after spending stamina it starts regenerating.
+const STAMINA_REGEN_DELAY_SECS: f32 = 0.75;
+/// After running out of stamina, sprinting only becomes possible again once this fraction has regenerated.
+const EXHAUSTION_RECOVERY_FRACTION: f32 = 0.3;
+
 #[derive(Component, Reflect, Default)]
 #[reflect(Component)]
 struct AccumulatedInput {
     last_move: Option<Vec3>,
+    sprint: bool,
+    crouch: bool,
+    /// Slides and dashes are requested once per button press and consumed by the next fixed update.
+    slide: bool,
+    dash: bool,
 }
 
-#[derive(Component, Reflect)]
+#[derive(Component, Clone, Reflect)]
 #[reflect(Component)]
 pub(crate) struct MovementStats {
     pub(crate) speed_factor: f32,
+    /// The speed multiplier while sprinting, on top of [`MovementStats::speed_factor`].
+    pub(crate) sprint_factor: f32,
+    /// The speed multiplier while crouching, on top of [`MovementStats::speed_factor`].
+    pub(crate) crouch_factor: f32,
+    pub(crate) jump_height: f32,
+    pub(crate) max_stamina: f32,
+    /// Stamina regenerated per second when not spending any.
+    pub(crate) stamina_regen: f32,
+    /// Stamina spent per second of sprinting.
+    pub(crate) sprint_cost: f32,
+    /// The speed added to the player's momentum when starting a slide.
+    pub(crate) slide_boost: f32,
+    /// How far an air dash carries the player.
+    pub(crate) dash_distance: f32,
+    pub(crate) dash_cooldown_secs: f32,
+    pub(crate) dash_cost: f32,
 }
 
 impl Default for MovementStats {
     fn default() -> Self {
-        Self { speed_factor: 1.0 }
+        Self {
+            speed_factor: 1.0,
+            sprint_factor: 1.6,
+            crouch_factor: 0.5,
+            jump_height: 1.5,
+            max_stamina: 100.0,
+            stamina_regen: 30.0,
+            sprint_cost: 20.0,
+            slide_boost: 4.0,
+            dash_distance: 5.0,
+            dash_cooldown_secs: 1.5,
+            dash_cost: 25.0,
+        }
+    }
+}
+
+/// An event that is triggered on the player to shove them, e.g. by an explosion.
+///
+/// Setting the velocity directly would fight the float spring and the walk basis,
+/// so the shove goes through Tnua's knockback action instead.
+#[derive(Event, Debug, Clone, Copy)]
+pub(crate) struct OnKnockback {
+    /// The change in velocity.
+    pub(crate) shove: Vec3,
+}
+
+/// What the player is currently doing beyond walking around.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub(crate) struct MovementState {
+    pub(crate) sprinting: bool,
+    pub(crate) crouching: bool,
+    /// The velocity of the current slide. It decays until the slide ends.
+    pub(crate) slide: Option<Vec3>,
+    /// Only one air dash is allowed per jump or fall.
+    air_dash_used: bool,
+    dash_cooldown: Timer,
+}
+
+impl Default for MovementState {
+    fn default() -> Self {
+        let mut dash_cooldown = Timer::from_seconds(0.0, TimerMode::Once);
+        dash_cooldown.tick(dash_cooldown.duration());
+        Self {
+            sprinting: false,
+            crouching: false,
+            slide: None,
+            air_dash_used: false,
+            dash_cooldown,
+        }
+    }
+}
+
+/// Spent on sprinting and dashing. The maximum is [`MovementStats::max_stamina`].
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub(crate) struct Stamina {
+    pub(crate) current: f32,
+    regen_delay: Timer,
+    /// Set when stamina runs out, so that the player can't stutter-sprint on an empty tank.
+    exhausted: bool,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            current: MovementStats::default().max_stamina,
+            regen_delay: Timer::from_seconds(STAMINA_REGEN_DELAY_SECS, TimerMode::Once),
+            exhausted: false,
+        }
+    }
+}
+
+impl Stamina {
+    /// Spends `amount` of stamina if there is enough left.
+    fn try_spend(&mut self, amount: f32) -> bool {
+        if self.exhausted || self.current < amount {
+            return false;
+        }
+        self.spend(amount);
+        true
+    }
+
+    /// Spends up to `amount` of stamina, exhausting the player if it runs out.
+    fn spend(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.0);
+        self.exhausted |= self.current == 0.0;
+        self.regen_delay.reset();
+    }
+
+    fn regenerate(&mut self, stats: &MovementStats, time: &Time) {
+        self.regen_delay.tick(time.delta());
+        if !self.regen_delay.finished() {
+            return;
+        }
+        self.current =
+            (self.current + stats.stamina_regen * time.delta_secs()).min(stats.max_stamina);
+        if self.current >= stats.max_stamina * EXHAUSTION_RECOVERY_FRACTION {
+            self.exhausted = false;
+        }
     }
 }
 
@@ -62,31 +202,174 @@ fn accumulate_movement(
     accumulated_inputs.last_move.replace(trigger.value);
 }
 
+#[cfg_attr(feature = "hot_patch", hot)]
+fn accumulate_sprint(
+    _trigger: Trigger<Fired<Sprint>>,
+    mut accumulated_inputs: Single<&mut AccumulatedInput>,
+) {
+    accumulated_inputs.sprint = true;
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn accumulate_crouch(
+    _trigger: Trigger<Fired<Crouch>>,
+    mut accumulated_inputs: Single<&mut AccumulatedInput>,
+) {
+    accumulated_inputs.crouch = true;
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn request_slide(
+    _trigger: Trigger<Started<Slide>>,
+    mut accumulated_inputs: Single<&mut AccumulatedInput>,
+) {
+    accumulated_inputs.slide = true;
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn request_dash(
+    _trigger: Trigger<Started<Dash>>,
+    mut accumulated_inputs: Single<&mut AccumulatedInput>,
+) {
+    accumulated_inputs.dash = true;
+}
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn clear_accumulated_input(mut accumulated_inputs: Query<&mut AccumulatedInput>) {
     for mut accumulated_input in &mut accumulated_inputs {
-        accumulated_input.last_move = None;
+        *accumulated_input = AccumulatedInput::default();
     }
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn apply_movement(
-    player_controller: Single<(&mut TnuaController, &AccumulatedInput, &MovementStats)>,
+    player_controller: Single<(
+        Entity,
+        &mut TnuaController,
+        &mut Collider,
+        &mut MovementState,
+        &mut Stamina,
+        &AccumulatedInput,
+        &MovementStats,
+        &Transform,
+        &LinearVelocity,
+        Option<&StatusEffects>,
+    )>,
     transform: Single<&Transform, With<PlayerCamera>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
 ) {
-    let (mut controller, accumulated_input, movement_stats) = player_controller.into_inner();
+    let (
+        entity,
+        mut controller,
+        mut collider,
+        mut state,
+        mut stamina,
+        accumulated_input,
+        movement_stats,
+        player_transform,
+        velocity,
+        status_effects,
+    ) = player_controller.into_inner();
+    let stunned = status_effects.is_some_and(StatusEffects::is_stunned);
+    let speed_factor =
+        movement_stats.speed_factor * status_effects.map_or(1.0, StatusEffects::speed_factor);
     let last_move = accumulated_input.last_move.unwrap_or_default();
+    let grounded = controller.is_airborne().is_ok_and(|airborne| !airborne);
+    let yaw = transform.rotation.to_euler(EulerRot::YXZ).0;
+    let yaw_quat = Quat::from_axis_angle(Vec3::Y, yaw);
+    let move_direction = yaw_quat * last_move;
+    let horizontal_velocity = velocity.0.with_y(0.0);
+    state.dash_cooldown.tick(time.delta());
+    if grounded {
+        state.air_dash_used = false;
+    }
+
+    // Slides carry the player's momentum and slowly bleed it off.
+    if accumulated_input.slide && state.slide.is_none() && grounded && !stunned {
+        let speed = horizontal_velocity.length();
+        if speed >= MIN_SLIDE_SPEED {
+            state.slide = Some(horizontal_velocity / speed * (speed + movement_stats.slide_boost));
+        }
+    }
+    if let Some(slide) = state.slide {
+        let speed = slide.length() - SLIDE_DECELERATION * time.delta_secs();
+        state.slide = (grounded && speed >= MIN_SLIDE_SPEED && !stunned)
+            .then(|| slide.normalize_or_zero() * speed);
+    }
+
+    // Standing up needs room above the player's head.
+    let wants_crouch = accumulated_input.crouch || state.slide.is_some();
+    let crouching = wants_crouch
+        || (state.crouching && !can_stand_up(entity, player_transform, &spatial_query));
+    if crouching != state.crouching {
+        state.crouching = crouching;
+        let length = if crouching {
+            PLAYER_CAPSULE_LENGTH - CROUCH_HEIGHT_OFFSET
+        } else {
+            PLAYER_CAPSULE_LENGTH
+        };
+        *collider = Collider::capsule(PLAYER_RADIUS, length);
+    }
+
+    let moving = move_direction.length_squared() > 0.0;
+    state.sprinting = accumulated_input.sprint
+        && moving
+        && !crouching
+        && !stunned
+        && !stamina.exhausted
+        && stamina.current > 0.0;
+    if state.sprinting {
+        stamina.spend(movement_stats.sprint_cost * time.delta_secs());
+    }
+
+    if accumulated_input.dash
+        && !grounded
+        && !stunned
+        && !state.air_dash_used
+        && state.dash_cooldown.finished()
+        && stamina.try_spend(movement_stats.dash_cost)
+    {
+        // Without movement input, dash the way the camera is facing.
+        let direction = Dir3::new(move_direction)
+            .or_else(|_| Dir3::new((yaw_quat * Vec3::NEG_Z).with_y(0.0)))
+            .unwrap_or(Dir3::NEG_Z);
+        controller.action(TnuaBuiltinDash {
+            displacement: direction * movement_stats.dash_distance,
+            allow_in_air: true,
+            ..default()
+        });
+        state.air_dash_used = true;
+        state.dash_cooldown =
+            Timer::from_seconds(movement_stats.dash_cooldown_secs, TimerMode::Once);
+    }
+
+    stamina.regenerate(movement_stats, &time);
+
+    let desired_velocity = if let Some(slide) = state.slide {
+        slide
+    } else if crouching {
+        move_direction * speed_factor * movement_stats.crouch_factor
+    } else if state.sprinting {
+        move_direction * speed_factor * movement_stats.sprint_factor
+    } else {
+        move_direction * speed_factor
+    };
+    let float_height = if crouching {
+        PLAYER_FLOAT_HEIGHT - CROUCH_HEIGHT_OFFSET
+    } else {
+        PLAYER_FLOAT_HEIGHT
+    };
+
     // Feed the basis every frame. Even if the player doesn't move - just use `desired_velocity:
     // Vec3::ZERO`. `TnuaController` starts without a basis, which will make the character collider
     // just fall.
-    let yaw = transform.rotation.to_euler(EulerRot::YXZ).0;
-    let yaw_quat = Quat::from_axis_angle(Vec3::Y, yaw);
     controller.basis(TnuaBuiltinWalk {
         // The `desired_velocity` determines how the character will move.
-        desired_velocity: yaw_quat * last_move * movement_stats.speed_factor,
+        desired_velocity,
         // The `float_height` must be greater (even if by little) from the distance between the
         // character's center and the lowest point of its collider.
-        float_height: PLAYER_FLOAT_HEIGHT,
+        float_height,
         // Restrict the max slope so that the player cannot walk up slightly angled chairs.
         max_slope: TAU / 8.0,
         spring_strength: 700.0,
@@ -94,13 +377,57 @@ fn apply_movement(
     });
 }
 
+/// Whether the player has enough room above their head to stop crouching.
+fn can_stand_up(entity: Entity, transform: &Transform, spatial_query: &SpatialQuery) -> bool {
+    // Standing up raises the player's center and lengthens the capsule by the same amount,
+    // so the top of the standing capsule ends up at this height above the crouching center.
+    let standing_top = CROUCH_HEIGHT_OFFSET + PLAYER_CAPSULE_LENGTH / 2.0 + PLAYER_RADIUS;
+    let probe_radius = PLAYER_RADIUS - 0.05;
+    let filter = SpatialQueryFilter::default()
+        .with_mask([CollisionLayer::Default, CollisionLayer::Prop])
+        .with_excluded_entities([entity]);
+    spatial_query
+        .cast_shape(
+            &Collider::sphere(probe_radius),
+            transform.translation,
+            Quat::IDENTITY,
+            Dir3::Y,
+            &ShapeCastConfig::from_max_distance(standing_top - probe_radius),
+            &filter,
+        )
+        .is_none()
+}
+
 #[cfg_attr(feature = "hot_patch", hot)]
-fn jump(trigger: Trigger<Fired<Jump>>, mut controllers: Query<&mut TnuaController>) {
-    let mut controller = controllers.get_mut(trigger.target()).unwrap();
+fn jump(
+    trigger: Trigger<Fired<Jump>>,
+    mut controllers: Query<(&mut TnuaController, &MovementStats, Option<&StatusEffects>)>,
+) {
+    let (mut controller, movement_stats, status_effects) =
+        controllers.get_mut(trigger.target()).unwrap();
+    if status_effects.is_some_and(StatusEffects::is_stunned) {
+        return;
+    }
     controller.action(TnuaBuiltinJump {
         // The height is the only mandatory field of the jump button.
-        height: 1.5,
+        height: movement_stats.jump_height,
         // `TnuaBuiltinJump` also has customization fields with sensible defaults.
         ..default()
     });
 }
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn knockback(
+    trigger: Trigger<OnKnockback>,
+    mut controllers: Query<(&mut TnuaController, &mut MovementState)>,
+) {
+    let Ok((mut controller, mut state)) = controllers.get_mut(trigger.target()) else {
+        return;
+    };
+    // Otherwise a slide would keep steering the player along the ground.
+    state.slide = None;
+    controller.action(TnuaBuiltinKnockback {
+        shove: trigger.shove,
+        ..default()
+    });
+}
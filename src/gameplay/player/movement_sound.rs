@@ -6,9 +6,14 @@ use bevy::prelude::*;
 use bevy_simple_subsecond_system::hot;
 use bevy_tnua::{builtins::TnuaBuiltinJumpState, prelude::*};
 
-use crate::{PostPhysicsAppSystems, audio::sound_effect, screens::Screen};
+use crate::{
+    PostPhysicsAppSystems,
+    audio::sound_effect,
+    gameplay::surface::{SurfaceAssets, SurfaceEvent, SurfaceMaterial, Surfaces},
+    screens::Screen,
+};
 
-use super::{Player, assets::PlayerAssets};
+use super::{GroundCast, Player, assets::PlayerAssets};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
@@ -58,8 +63,9 @@ fn play_jump_grunt(
 #[cfg_attr(feature = "hot_patch", hot)]
 fn play_step_sound(
     mut commands: Commands,
-    player: Single<(&TnuaController, &LinearVelocity), With<Player>>,
-    mut player_assets: ResMut<PlayerAssets>,
+    player: Single<(&TnuaController, &LinearVelocity, &Transform, &GroundCast), With<Player>>,
+    mut surface_assets: ResMut<SurfaceAssets>,
+    mut surfaces: Surfaces,
     time: Res<Time>,
     mut timer: Local<Option<Timer>>,
 ) {
@@ -70,26 +76,28 @@ fn play_step_sound(
         return;
     }
 
-    let (controller, linear_velocity) = player.into_inner();
+    let (controller, linear_velocity, transform, ground_cast) = player.into_inner();
     if controller.is_airborne().unwrap_or(true) {
         return;
     }
     if linear_velocity.length_squared() < 5.0 {
         return;
     }
+    let surface = ground_surface(transform, ground_cast, &mut surfaces);
     let rng = &mut rand::thread_rng();
-    let sound = player_assets.steps.pick(rng).clone();
-    commands.spawn(sound_effect(sound));
+    commands.spawn(surface.sound(SurfaceEvent::Step, &mut surface_assets, rng));
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn play_land_sound(
     mut commands: Commands,
-    player: Single<&TnuaController, With<Player>>,
-    mut player_assets: ResMut<PlayerAssets>,
+    player: Single<(&TnuaController, &Transform, &GroundCast), With<Player>>,
+    mut surface_assets: ResMut<SurfaceAssets>,
+    mut surfaces: Surfaces,
     mut was_airborne: Local<bool>,
 ) {
-    let is_airborne = player.is_airborne().unwrap_or(true);
+    let (controller, transform, ground_cast) = player.into_inner();
+    let is_airborne = controller.is_airborne().unwrap_or(true);
     if is_airborne {
         *was_airborne = true;
         return;
@@ -99,7 +107,18 @@ fn play_land_sound(
     }
     *was_airborne = false;
 
+    let surface = ground_surface(transform, ground_cast, &mut surfaces);
     let rng = &mut rand::thread_rng();
-    let sound = player_assets.land_sounds.pick(rng).clone();
-    commands.spawn(sound_effect(sound));
+    commands.spawn(surface.sound(SurfaceEvent::Land, &mut surface_assets, rng));
+}
+
+/// The surface the player is standing on.
+fn ground_surface(
+    transform: &Transform,
+    ground_cast: &GroundCast,
+    surfaces: &mut Surfaces,
+) -> SurfaceMaterial {
+    ground_cast.map_or(SurfaceMaterial::default(), |hit| {
+        surfaces.surface_of(hit.entity, Ray3d::new(transform.translation, Dir3::NEG_Y))
+    })
 }
@@ -5,7 +5,7 @@ use bevy_landmass::{Archipelago3d, FromAgentRadius, PointSampleDistance3d};
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
 
-use crate::PrePhysicsAppSystems;
+use crate::{PrePhysicsAppSystems, third_party::bevy_landmass::BaseArchipelago};
 
 use super::PLAYER_RADIUS;
 
@@ -24,7 +24,7 @@ pub(crate) struct LastValidPlayerNavmeshPosition(pub(crate) Option<Vec3>);
 #[cfg_attr(feature = "hot_patch", hot)]
 fn update_last_valid_player_navmesh_position(
     player_character: Single<(&GlobalTransform, &mut LastValidPlayerNavmeshPosition)>,
-    archipelago: Single<&Archipelago3d>,
+    archipelago: Single<&Archipelago3d, With<BaseArchipelago>>,
     time: Res<Time>,
     mut timer: Local<Option<Timer>>,
 ) {
@@ -0,0 +1,245 @@
+//! Lives, respawning and checkpoints.
+//!
+//! Both lives and checkpoints are optional. Without lives, the first death ends the game,
+//! and without checkpoints, trying again starts over at the first wave.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use bevy_trenchbroom::prelude::*;
+
+use crate::{
+    gameplay::{
+        health::{Armor, Health, Invulnerable, OnDeath, Regeneration, Shield},
+        npc::Npc,
+        player::{
+            PLAYER_MAX_HEALTH, Player,
+            gunplay::{Ammo, WeaponStats},
+            movement::{MovementStats, Stamina},
+        },
+        status_effects::StatusEffects,
+        waves::{WaveAdvanced, Waves},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(
+        RespawnSettings,
+        Lives,
+        PlayerRespawn,
+        PlayerSpawnPoint,
+        Checkpoint,
+    )>();
+    app.init_resource::<RespawnSettings>();
+    app.add_observer(setup_lives);
+    app.add_observer(respawn_or_game_over);
+    app.add_observer(take_checkpoint);
+    app.add_systems(OnEnter(Screen::Gameplay), restore_checkpoint);
+    app.add_systems(OnEnter(Screen::Title), clear_checkpoint);
+}
+
+/// How long the player can't be damaged after respawning.
+const RESPAWN_INVULNERABILITY_SECS: f32 = 3.0;
+
+#[derive(Resource, Reflect, Clone, Default)]
+#[reflect(Resource)]
+pub(crate) struct RespawnSettings {
+    /// How many lives the player starts with. `None` disables lives, so that the first death ends the game.
+    pub(crate) lives: Option<u32>,
+    /// How many waves apart checkpoints are taken. `None` disables checkpoints.
+    pub(crate) checkpoint_every: Option<u32>,
+}
+
+/// How many lives the player has left, including the current one.
+#[derive(Component, Reflect, Debug, Clone, Copy, Deref, DerefMut)]
+#[reflect(Component)]
+pub(crate) struct Lives(pub(crate) u32);
+
+/// A place where the player respawns after losing a life.
+/// If a level has several, the player respawns at the one furthest away from the enemies.
+/// It has no model, so that it isn't mistaken for the player start in TrenchBroom.
+#[derive(PointClass, Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+pub(crate) struct PlayerRespawn;
+
+/// Where the player was placed in the level.
+/// Used for respawning in levels without a [`PlayerRespawn`].
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct PlayerSpawnPoint(Vec3);
+
+/// Triggered when the player dies without any lives left.
+#[derive(Event)]
+pub(crate) struct GameOver;
+
+/// A snapshot of the game at the start of a wave, which "Try Again" resumes from.
+#[derive(Resource, Reflect, Clone)]
+#[reflect(Resource)]
+pub(crate) struct Checkpoint {
+    /// The number of the wave the checkpoint resumes at, starting at 1.
+    pub(crate) wave: usize,
+    waves: Waves,
+    lives: Option<Lives>,
+    health: Health,
+    armor: Armor,
+    shield: Shield,
+    regeneration: Regeneration,
+    weapon_stats: WeaponStats,
+    movement_stats: MovementStats,
+}
+
+fn setup_lives(
+    trigger: Trigger<OnAdd, Player>,
+    transforms: Query<&Transform>,
+    settings: Res<RespawnSettings>,
+    mut commands: Commands,
+) {
+    let player = trigger.target();
+    let spawn_point = transforms
+        .get(player)
+        .map_or(Vec3::ZERO, |transform| transform.translation);
+    let mut player = commands.entity(player);
+    player.insert(PlayerSpawnPoint(spawn_point));
+    if let Some(lives) = settings.lives {
+        player.insert(Lives(lives));
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn respawn_or_game_over(
+    trigger: Trigger<OnDeath>,
+    mut player: Query<
+        (
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut Stamina,
+            &mut Shield,
+            &mut Ammo,
+            &MovementStats,
+            &PlayerSpawnPoint,
+            Option<&mut Lives>,
+        ),
+        With<Player>,
+    >,
+    respawn_points: Query<&Transform, (With<PlayerRespawn>, Without<Player>)>,
+    npcs: Query<&Transform, (With<Npc>, Without<Player>)>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    let Ok((
+        mut transform,
+        mut velocity,
+        mut stamina,
+        mut shield,
+        mut ammo,
+        movement_stats,
+        spawn_point,
+        lives,
+    )) = player.get_mut(entity)
+    else {
+        return;
+    };
+    let Some(mut lives) = lives.filter(|lives| lives.0 > 1) else {
+        commands.trigger(GameOver);
+        return;
+    };
+    lives.0 -= 1;
+
+    let distance_to_enemies = |point: Vec3| {
+        npcs.iter()
+            .map(|npc| npc.translation.distance_squared(point))
+            .fold(f32::INFINITY, f32::min)
+    };
+    transform.translation = respawn_points
+        .iter()
+        .map(|respawn_point| respawn_point.translation)
+        .max_by(|a, b| distance_to_enemies(*a).total_cmp(&distance_to_enemies(*b)))
+        .unwrap_or(spawn_point.0);
+    velocity.0 = Vec3::ZERO;
+    // Upgrades live in the player's stats, so they are kept. Only the things that run out are refilled.
+    stamina.current = movement_stats.max_stamina;
+    shield.current = shield.max;
+    ammo.refill_full();
+    commands
+        .entity(entity)
+        .insert((
+            Health::new(PLAYER_MAX_HEALTH),
+            Invulnerable::from_seconds(RESPAWN_INVULNERABILITY_SECS),
+        ))
+        .remove::<StatusEffects>();
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn take_checkpoint(
+    _trigger: Trigger<WaveAdvanced>,
+    waves: Single<&Waves>,
+    player: Single<
+        (
+            &Health,
+            &Armor,
+            &Shield,
+            &Regeneration,
+            &WeaponStats,
+            &MovementStats,
+            Option<&Lives>,
+        ),
+        With<Player>,
+    >,
+    settings: Res<RespawnSettings>,
+    mut commands: Commands,
+) {
+    let Some(checkpoint_every) = settings.checkpoint_every.filter(|every| *every > 0) else {
+        return;
+    };
+    let wave = waves.current_wave_index();
+    if waves.is_finished() || wave % checkpoint_every as usize != 0 {
+        return;
+    }
+    let (health, armor, shield, regeneration, weapon_stats, movement_stats, lives) =
+        player.into_inner();
+    commands.insert_resource(Checkpoint {
+        wave: wave + 1,
+        waves: waves.checkpoint(),
+        lives: lives.copied(),
+        health: health.clone(),
+        armor: armor.clone(),
+        shield: shield.clone(),
+        regeneration: regeneration.clone(),
+        weapon_stats: weapon_stats.clone(),
+        movement_stats: movement_stats.clone(),
+    });
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn restore_checkpoint(
+    checkpoint: Option<Res<Checkpoint>>,
+    mut waves: Single<&mut Waves>,
+    player: Single<Entity, With<Player>>,
+    mut commands: Commands,
+) {
+    let Some(checkpoint) = checkpoint else {
+        return;
+    };
+    **waves = checkpoint.waves.clone();
+    let mut player = commands.entity(*player);
+    player.insert((
+        checkpoint.health.clone(),
+        checkpoint.armor.clone(),
+        checkpoint.shield.clone(),
+        checkpoint.regeneration.clone(),
+        checkpoint.weapon_stats.clone(),
+        checkpoint.movement_stats.clone(),
+    ));
+    if let Some(lives) = checkpoint.lives {
+        player.insert(lives);
+    } else {
+        player.remove::<Lives>();
+    }
+}
+
+fn clear_checkpoint(mut commands: Commands) {
+    commands.remove_resource::<Checkpoint>();
+}
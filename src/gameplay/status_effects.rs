@@ -0,0 +1,237 @@
+//! Timed, stacking status effects such as burning or being slowed.
+//! These work on both NPCs and the player, as long as they have [`Health`].
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    PostPhysicsAppSystems,
+    gameplay::health::{DamageKind, Health, OnDamage},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(
+        StatusEffects,
+        StatusEffect,
+        StatusEffectKind,
+        StatusEffectSpec,
+    )>();
+    app.add_observer(apply_status_effect);
+    app.add_systems(
+        Update,
+        tick_status_effects
+            .in_set(PostPhysicsAppSystems::TickTimers)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How often damage over time is dealt.
+const DAMAGE_TICK_SECS: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub(crate) enum StatusEffectKind {
+    /// Deals damage over time.
+    Burning,
+    /// Reduces movement speed.
+    Slowed,
+    /// Prevents movement and attacks.
+    Stunned,
+    /// Deals damage over time and slightly reduces movement speed.
+    Bleeding,
+}
+
+impl StatusEffectKind {
+    pub(crate) fn max_stacks(self) -> u32 {
+        match self {
+            StatusEffectKind::Burning => 3,
+            StatusEffectKind::Slowed => 3,
+            StatusEffectKind::Stunned => 1,
+            StatusEffectKind::Bleeding => 5,
+        }
+    }
+
+    /// Damage per second dealt by a single stack.
+    fn damage_per_second(self) -> Option<(f32, DamageKind)> {
+        match self {
+            StatusEffectKind::Burning => Some((8.0, DamageKind::Burning)),
+            StatusEffectKind::Bleeding => Some((3.0, DamageKind::Bleeding)),
+            StatusEffectKind::Slowed | StatusEffectKind::Stunned => None,
+        }
+    }
+
+    /// Movement speed multiplier of a single stack.
+    fn speed_factor(self) -> f32 {
+        match self {
+            StatusEffectKind::Burning => 1.0,
+            StatusEffectKind::Slowed => 0.7,
+            StatusEffectKind::Stunned => 0.0,
+            StatusEffectKind::Bleeding => 0.95,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            StatusEffectKind::Burning => "Burning",
+            StatusEffectKind::Slowed => "Slowed",
+            StatusEffectKind::Stunned => "Stunned",
+            StatusEffectKind::Bleeding => "Bleeding",
+        }
+    }
+}
+
+/// A description of a status effect to apply, e.g. on a weapon or an explosive.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub(crate) struct StatusEffectSpec {
+    pub(crate) kind: StatusEffectKind,
+    pub(crate) duration_secs: f32,
+    /// How many stacks are added at once.
+    pub(crate) stacks: u32,
+}
+
+impl StatusEffectSpec {
+    pub(crate) fn new(kind: StatusEffectKind, duration_secs: f32) -> Self {
+        Self {
+            kind,
+            duration_secs,
+            stacks: 1,
+        }
+    }
+}
+
+/// An event that is triggered on an entity to apply a status effect to it.
+#[derive(Debug, Event, Clone, Copy)]
+pub(crate) struct OnStatusEffect {
+    pub(crate) spec: StatusEffectSpec,
+    /// The entity responsible for the effect. Damage over time is attributed to it.
+    pub(crate) instigator: Option<Entity>,
+}
+
+impl OnStatusEffect {
+    pub(crate) fn new(spec: StatusEffectSpec) -> Self {
+        Self {
+            spec,
+            instigator: None,
+        }
+    }
+
+    pub(crate) fn with_instigator(mut self, instigator: impl Into<Option<Entity>>) -> Self {
+        self.instigator = instigator.into();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct StatusEffect {
+    pub(crate) kind: StatusEffectKind,
+    pub(crate) stacks: u32,
+    pub(crate) remaining: Timer,
+    damage_tick: Timer,
+    instigator: Option<Entity>,
+}
+
+/// All status effects currently active on an entity.
+/// Inserted on demand the first time an effect is applied.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.0.iter()
+    }
+
+    pub(crate) fn has(&self, kind: StatusEffectKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub(crate) fn is_stunned(&self) -> bool {
+        self.has(StatusEffectKind::Stunned)
+    }
+
+    /// The factor by which movement speed is scaled by all active effects.
+    pub(crate) fn speed_factor(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|effect| effect.kind.speed_factor().powi(effect.stacks as i32))
+            .product()
+    }
+
+    fn add(&mut self, spec: StatusEffectSpec, instigator: Option<Entity>) {
+        let duration = Duration::from_secs_f32(spec.duration_secs);
+        if let Some(effect) = self.0.iter_mut().find(|effect| effect.kind == spec.kind) {
+            effect.stacks = (effect.stacks + spec.stacks).min(spec.kind.max_stacks());
+            // Refresh the duration, but never shorten it.
+            if effect.remaining.remaining() < duration {
+                effect.remaining = Timer::new(duration, TimerMode::Once);
+            }
+            effect.instigator = instigator.or(effect.instigator);
+        } else {
+            self.0.push(StatusEffect {
+                kind: spec.kind,
+                stacks: spec.stacks.min(spec.kind.max_stacks()),
+                remaining: Timer::new(duration, TimerMode::Once),
+                damage_tick: Timer::from_seconds(DAMAGE_TICK_SECS, TimerMode::Repeating),
+                instigator,
+            });
+        }
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn apply_status_effect(
+    trigger: Trigger<OnStatusEffect>,
+    mut targets: Query<Option<&mut StatusEffects>, With<Health>>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    let Ok(status_effects) = targets.get_mut(entity) else {
+        return;
+    };
+    let event = trigger.event();
+    if let Some(mut status_effects) = status_effects {
+        status_effects.add(event.spec, event.instigator);
+    } else {
+        let mut status_effects = StatusEffects::default();
+        status_effects.add(event.spec, event.instigator);
+        commands.entity(entity).insert(status_effects);
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn tick_status_effects(
+    mut status_effects: Query<(Entity, &mut StatusEffects), With<Health>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut status_effects) in &mut status_effects {
+        // Ticking the timers every frame would mark the effects as changed all the time,
+        // so only effects running out count as a change.
+        for effect in &mut status_effects.bypass_change_detection().0 {
+            effect.remaining.tick(time.delta());
+            let Some((damage_per_second, damage_kind)) = effect.kind.damage_per_second() else {
+                continue;
+            };
+            effect.damage_tick.tick(time.delta());
+            let ticks = effect.damage_tick.times_finished_this_tick();
+            if ticks == 0 {
+                continue;
+            }
+            let damage = damage_per_second * DAMAGE_TICK_SECS * effect.stacks as f32 * ticks as f32;
+            commands
+                .entity(entity)
+                .trigger(OnDamage::new(damage, damage_kind).with_instigator(effect.instigator));
+        }
+        if status_effects
+            .iter()
+            .any(|effect| effect.remaining.finished())
+        {
+            status_effects
+                .0
+                .retain(|effect| !effect.remaining.finished());
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! Surface materials. Footsteps, landings and bullet impacts sound and look different
+//! depending on what they happen on.
+//!
+//! Props, characters and gibs declare their [`SurfaceMaterial`] as a component.
+//! Map brushes share one collider per entity, so for them the mesh under the hit point is found
+//! with a mesh ray cast, and the surface is guessed from the name of its TrenchBroom texture.
+
+use std::f32::consts::TAU;
+
+use avian3d::prelude::*;
+use bevy::{
+    asset::{RenderAssetUsages, VisitAssetDependencies},
+    audio::Volume,
+    ecs::system::SystemParam,
+    picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastVisibility},
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_shuffle_bag::ShuffleBag;
+use bevy_trenchbroom::geometry::MapGeometry;
+use rand::Rng;
+
+use crate::{
+    asset_tracking::LoadResource,
+    audio::SoundEffect,
+    gameplay::{
+        explosion::assets::ExplosionAssets,
+        gore_settings::{Gore, GoreSettings},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(SurfaceMaterial, SurfaceAssets)>();
+    app.load_resource::<SurfaceAssets>();
+}
+
+/// What a surface is made of.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) enum SurfaceMaterial {
+    /// Also used for anything that doesn't say what it's made of.
+    #[default]
+    Stone,
+    Wood,
+    Metal,
+    Flesh,
+}
+
+/// Something that happens on a surface and makes a sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SurfaceEvent {
+    Step,
+    Land,
+    BulletImpact,
+}
+
+/// Words that identify the surface material in a texture name, matched against the start of each word.
+const TEXTURE_KEYWORDS: [(&str, SurfaceMaterial); 17] = [
+    ("wood", SurfaceMaterial::Wood),
+    ("plank", SurfaceMaterial::Wood),
+    ("board", SurfaceMaterial::Wood),
+    ("crate", SurfaceMaterial::Wood),
+    ("bark", SurfaceMaterial::Wood),
+    ("metal", SurfaceMaterial::Metal),
+    ("steel", SurfaceMaterial::Metal),
+    ("iron", SurfaceMaterial::Metal),
+    ("rust", SurfaceMaterial::Metal),
+    ("grate", SurfaceMaterial::Metal),
+    ("pipe", SurfaceMaterial::Metal),
+    ("flesh", SurfaceMaterial::Flesh),
+    ("blood", SurfaceMaterial::Flesh),
+    ("gore", SurfaceMaterial::Flesh),
+    ("stone", SurfaceMaterial::Stone),
+    ("rock", SurfaceMaterial::Stone),
+    ("brick", SurfaceMaterial::Stone),
+];
+
+impl SurfaceMaterial {
+    /// Guesses the material from a texture name like `wood/planks_01`.
+    pub(crate) fn from_texture_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        name.split(|c: char| !c.is_ascii_alphabetic())
+            .find_map(|word| {
+                TEXTURE_KEYWORDS
+                    .iter()
+                    .find(|(keyword, _)| word.starts_with(keyword))
+            })
+            .map(|(_, surface)| *surface)
+    }
+
+    /// The sound of `event` happening on this surface.
+    pub(crate) fn sound(
+        self,
+        event: SurfaceEvent,
+        surface_assets: &mut SurfaceAssets,
+        rng: &mut impl Rng,
+    ) -> impl Bundle {
+        let sounds = surface_assets.sounds_mut(self);
+        let (speed, volume) = (sounds.speed, sounds.volume);
+        let bag = match event {
+            SurfaceEvent::Step => &mut sounds.steps,
+            SurfaceEvent::Land => &mut sounds.landings,
+            SurfaceEvent::BulletImpact => &mut sounds.impacts,
+        };
+        (
+            AudioPlayer(bag.pick(rng).clone()),
+            PlaybackSettings::DESPAWN
+                .with_speed(speed)
+                .with_volume(Volume::Linear(volume)),
+            SoundEffect,
+        )
+    }
+}
+
+/// Finds out what surface something hit.
+#[derive(SystemParam)]
+pub(crate) struct Surfaces<'w, 's> {
+    declared: Query<'w, 's, &'static SurfaceMaterial>,
+    collider_of: Query<'w, 's, &'static ColliderOf>,
+    parents: Query<'w, 's, &'static ChildOf>,
+    children: Query<'w, 's, &'static Children>,
+    geometry: Query<'w, 's, &'static MeshMaterial3d<StandardMaterial>, With<MapGeometry>>,
+    materials: Res<'w, Assets<StandardMaterial>>,
+    asset_server: Res<'w, AssetServer>,
+    mesh_ray_cast: MeshRayCast<'w, 's>,
+    /// Texture names don't change, so the surface of every map material only needs to be guessed once.
+    map_surfaces: Local<'s, HashMap<AssetId<StandardMaterial>, SurfaceMaterial>>,
+}
+
+impl Surfaces<'_, '_> {
+    /// The surface of `collider` where `ray` hits it.
+    pub(crate) fn surface_of(&mut self, collider: Entity, ray: Ray3d) -> SurfaceMaterial {
+        let declared = self
+            .declared
+            .get(collider)
+            .ok()
+            .or_else(|| {
+                self.parents
+                    .iter_ancestors(collider)
+                    .find_map(|ancestor| self.declared.get(ancestor).ok())
+            })
+            .copied();
+        if let Some(surface) = declared {
+            return surface;
+        }
+
+        // Only look at the map geometry belonging to the body that was hit.
+        let body = self
+            .collider_of
+            .get(collider)
+            .map_or(collider, |collider_of| collider_of.body);
+        let candidates = self
+            .children
+            .iter_descendants(body)
+            .filter(|entity| self.geometry.contains(*entity))
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return SurfaceMaterial::default();
+        }
+        let filter = |entity: Entity| candidates.contains(&entity);
+        let settings = MeshRayCastSettings::default()
+            .with_visibility(RayCastVisibility::Any)
+            .with_filter(&filter);
+        let Some(&(mesh, _)) = self.mesh_ray_cast.cast_ray(ray, &settings).first() else {
+            return SurfaceMaterial::default();
+        };
+        let Ok(material) = self.geometry.get(mesh) else {
+            return SurfaceMaterial::default();
+        };
+        if let Some(surface) = self.map_surfaces.get(&material.id()) {
+            return *surface;
+        }
+        let surface = self.guess_map_surface(material.id());
+        self.map_surfaces.insert(material.id(), surface);
+        surface
+    }
+
+    /// Guesses the surface from the name of the material or its texture, whichever says more.
+    fn guess_map_surface(&self, material: AssetId<StandardMaterial>) -> SurfaceMaterial {
+        let texture = self
+            .materials
+            .get(material)
+            .and_then(|material| material.base_color_texture.as_ref())
+            .and_then(|texture| self.asset_server.get_path(texture.id()));
+        [self.asset_server.get_path(material), texture]
+            .into_iter()
+            .flatten()
+            .find_map(|path| SurfaceMaterial::from_texture_name(&path.path().to_string_lossy()))
+            .unwrap_or_default()
+    }
+}
+
+/// The sounds of every [`SurfaceEvent`] on one surface.
+#[derive(Clone, Reflect, VisitAssetDependencies)]
+pub(crate) struct SurfaceSounds {
+    #[dependency]
+    steps: ShuffleBag<Handle<AudioSource>>,
+    #[dependency]
+    landings: ShuffleBag<Handle<AudioSource>>,
+    #[dependency]
+    impacts: ShuffleBag<Handle<AudioSource>>,
+    /// Playback tuning, so that surfaces sharing recordings still sound apart.
+    speed: f32,
+    volume: f32,
+}
+
+/// The sounds of every surface, and the materials for bullet holes, which are generated so that they can be tinted per surface.
+///
+/// We only have footstep recordings for stone and generic impact recordings so far.
+/// Until there are proper ones, every surface uses them, told apart by its playback speed and volume.
+/// Each surface has its own bags, so adding recordings only means changing the paths it loads.
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct SurfaceAssets {
+    #[dependency]
+    stone: SurfaceSounds,
+    #[dependency]
+    wood: SurfaceSounds,
+    #[dependency]
+    metal: SurfaceSounds,
+    #[dependency]
+    flesh: SurfaceSounds,
+    bullet_holes: HashMap<SurfaceMaterial, Handle<StandardMaterial>>,
+}
+
+const ROCK_STEPS: [&str; 9] = [
+    "audio/sound_effects/step/Footsteps_Rock_Walk_01.ogg",
+    "audio/sound_effects/step/Footsteps_Rock_Walk_02.ogg",
+    "audio/sound_effects/step/Footsteps_Rock_Walk_03.ogg",
+    "audio/sound_effects/step/Footsteps_Rock_Walk_04.ogg",
+    "audio/sound_effects/step/Footsteps_Rock_Walk_05.ogg",
+    "audio/sound_effects/step/Footsteps_Rock_Walk_06.ogg",
+    "audio/sound_effects/step/Footsteps_Rock_Walk_07.ogg",
+    "audio/sound_effects/step/Footsteps_Rock_Walk_08.ogg",
+    "audio/sound_effects/step/Footsteps_Rock_Walk_09.ogg",
+];
+const ROCK_LANDINGS: [&str; 6] = [
+    "audio/sound_effects/land/Footsteps_Rock_Jump_Land_01.ogg",
+    "audio/sound_effects/land/Footsteps_Rock_Jump_Land_02.ogg",
+    "audio/sound_effects/land/Footsteps_Rock_Jump_Land_03.ogg",
+    "audio/sound_effects/land/Footsteps_Rock_Jump_Land_04.ogg",
+    "audio/sound_effects/land/Footsteps_Rock_Jump_Land_05.ogg",
+    "audio/sound_effects/land/Footsteps_Rock_Jump_Land_06.ogg",
+];
+const IMPACTS: [&str; 3] = [
+    "audio/sound_effects/impact/Impact02.ogg",
+    "audio/sound_effects/impact/Impact04.ogg",
+    "audio/sound_effects/impact/Impact08.ogg",
+];
+
+impl FromWorld for SurfaceAssets {
+    fn from_world(world: &mut World) -> Self {
+        let rng = &mut rand::thread_rng();
+        let assets = world.resource::<AssetServer>();
+        let mut bag = |paths: &[&'static str]| {
+            ShuffleBag::try_new(
+                paths
+                    .iter()
+                    .map(|path| assets.load(*path))
+                    .collect::<Vec<_>>(),
+                rng,
+            )
+            .unwrap()
+        };
+        let mut sounds = |speed: f32, volume: f32| SurfaceSounds {
+            steps: bag(&ROCK_STEPS),
+            landings: bag(&ROCK_LANDINGS),
+            impacts: bag(&IMPACTS),
+            speed,
+            volume,
+        };
+        let stone = sounds(1.0, 1.0);
+        let wood = sounds(0.8, 0.9);
+        let metal = sounds(1.35, 1.1);
+        let flesh = sounds(0.7, 0.8);
+
+        let texture = world.add_asset(bullet_hole_image());
+        let bullet_holes = [
+            (SurfaceMaterial::Stone, Color::srgb(0.3, 0.28, 0.26), 0.0),
+            (SurfaceMaterial::Wood, Color::srgb(0.25, 0.16, 0.09), 0.0),
+            // Bullets scuff metal bright instead of leaving a dark hole.
+            (SurfaceMaterial::Metal, Color::srgb(0.75, 0.75, 0.78), 0.9),
+        ]
+        .into_iter()
+        .map(|(surface, base_color, metallic)| {
+            let material = world.add_asset(StandardMaterial {
+                base_color,
+                base_color_texture: Some(texture.clone()),
+                perceptual_roughness: 0.8,
+                metallic,
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            });
+            (surface, material)
+        })
+        .collect();
+        Self {
+            stone,
+            wood,
+            metal,
+            flesh,
+            bullet_holes,
+        }
+    }
+}
+
+impl SurfaceAssets {
+    fn sounds_mut(&mut self, surface: SurfaceMaterial) -> &mut SurfaceSounds {
+        match surface {
+            SurfaceMaterial::Stone => &mut self.stone,
+            SurfaceMaterial::Wood => &mut self.wood,
+            SurfaceMaterial::Metal => &mut self.metal,
+            SurfaceMaterial::Flesh => &mut self.flesh,
+        }
+    }
+
+    /// Picks the decal for a bullet hitting `surface`. Flesh bleeds, if the gore settings allow it.
+    pub(crate) fn bullet_decal(
+        &self,
+        surface: SurfaceMaterial,
+        explosion_assets: &mut ExplosionAssets,
+        gore_settings: &GoreSettings,
+        rng: &mut impl Rng,
+    ) -> Option<Handle<StandardMaterial>> {
+        match surface {
+            SurfaceMaterial::Flesh if gore_settings.blood_decals == Gore::None => None,
+            SurfaceMaterial::Flesh => Some(explosion_assets.blood_splatter.pick(rng).clone()),
+            _ => self.bullet_holes.get(&surface).cloned(),
+        }
+    }
+}
+
+/// Where to spray a bullet decal so that it is projected onto the surface at `point` with the given `normal`.
+pub(crate) fn bullet_decal_transform(
+    surface: SurfaceMaterial,
+    point: Vec3,
+    normal: Vec3,
+    rng: &mut impl Rng,
+) -> Transform {
+    let size = match surface {
+        SurfaceMaterial::Flesh => rng.gen_range(0.3..0.5),
+        _ => rng.gen_range(0.08..0.14),
+    };
+    // Decals are projected along their local -Z axis.
+    let rotation = Quat::from_rotation_arc(Vec3::NEG_Z, -normal.normalize_or(Vec3::Y))
+        * Quat::from_rotation_z(rng.gen_range(0.0..TAU));
+    Transform::from_translation(point)
+        .with_rotation(rotation)
+        .with_scale(Vec3::new(size, size, 0.2))
+}
+
+/// A round hole with a soft, ragged rim. White, so that materials can tint it.
+fn bullet_hole_image() -> Image {
+    const SIZE: u32 = 64;
+    let rng = &mut rand::thread_rng();
+    // Each angle gets its own rim radius, so that the holes aren't perfect circles.
+    let rim = (0..16)
+        .map(|_| rng.gen_range(0.75..1.0))
+        .collect::<Vec<f32>>();
+
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let offset = (Vec2::new(x as f32, y as f32) + 0.5) / SIZE as f32 * 2.0 - 1.0;
+            let angle = offset.y.atan2(offset.x).rem_euclid(TAU) / TAU * rim.len() as f32;
+            let radius = rim[angle as usize % rim.len()];
+            let distance = offset.length() / radius;
+            // A dark core that fades into a lighter rim.
+            let value = (0.1 + 0.9 * distance.clamp(0.0, 1.0)) * 255.0;
+            let alpha = (1.0 - distance).clamp(0.0, 0.25) * 4.0 * 255.0;
+            data.extend_from_slice(&[value as u8, value as u8, value as u8, alpha as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
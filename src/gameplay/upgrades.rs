@@ -1,4 +1,4 @@
-use std::{any::Any, iter::once};
+use std::{any::Any, iter::once, time::Duration};
 
 use bevy::prelude::*;
 use bevy_enhanced_input::prelude::*;
@@ -9,13 +9,14 @@ use crate::{
     font::FontAssets,
     gameplay::{
         crosshair::CrosshairState,
-        health::Health,
+        health::{Armor, Health, Regeneration, Shield},
         player::{
             Player,
             default_input::{BlocksInput, OpenUpgradeMenu},
             gunplay::WeaponStats,
-            movement::MovementStats,
+            movement::{MovementStats, Stamina},
         },
+        status_effects::{StatusEffectKind, StatusEffectSpec},
         waves::{WaveFinishedPreparing, WaveStartedPreparing},
     },
     screens::Screen,
@@ -45,7 +46,14 @@ pub(crate) enum Upgrade {
     Accuracy,
     BulletCount,
     JumpShotPushback,
+    BlastJump,
     EnemyExplosionRadius,
+    IncendiaryShells,
+    Armor,
+    Shield,
+    Regeneration,
+    Stamina,
+    AirDash,
 }
 impl Upgrade {
     fn all_except_health() -> Vec<Upgrade> {
@@ -56,7 +64,14 @@ impl Upgrade {
             Upgrade::Accuracy,
             Upgrade::BulletCount,
             Upgrade::JumpShotPushback,
+            Upgrade::BlastJump,
             Upgrade::EnemyExplosionRadius,
+            Upgrade::IncendiaryShells,
+            Upgrade::Armor,
+            Upgrade::Shield,
+            Upgrade::Regeneration,
+            Upgrade::Stamina,
+            Upgrade::AirDash,
         ]
     }
 }
@@ -128,11 +143,44 @@ fn spawn_upgrade_ui(
                 fonts.default.clone(),
                 upgrade_jump_shot_pushback,
             )),
+            Upgrade::BlastJump => menu_commands.with_child(button(
+                "Blast Jumping",
+                fonts.default.clone(),
+                upgrade_blast_jump,
+            )),
             Upgrade::EnemyExplosionRadius => menu_commands.with_child(button(
                 "Larger Enemy Explosion",
                 fonts.default.clone(),
                 upgrade_enemy_explosion_radius,
             )),
+            Upgrade::IncendiaryShells => menu_commands.with_child(button(
+                "Incendiary Shells",
+                fonts.default.clone(),
+                upgrade_incendiary_shells,
+            )),
+            Upgrade::Armor => {
+                menu_commands.with_child(button("More Armor", fonts.default.clone(), upgrade_armor))
+            }
+            Upgrade::Shield => menu_commands.with_child(button(
+                "Stronger Shield",
+                fonts.default.clone(),
+                upgrade_shield,
+            )),
+            Upgrade::Regeneration => menu_commands.with_child(button(
+                "Health Regeneration",
+                fonts.default.clone(),
+                upgrade_regeneration,
+            )),
+            Upgrade::Stamina => menu_commands.with_child(button(
+                "More Stamina",
+                fonts.default.clone(),
+                upgrade_stamina,
+            )),
+            Upgrade::AirDash => menu_commands.with_child(button(
+                "Longer Air Dash",
+                fonts.default.clone(),
+                upgrade_air_dash,
+            )),
         };
     }
 }
@@ -166,6 +214,28 @@ fn upgrade_speed(
     commands.trigger(DespawnUpgrades);
 }
 
+fn upgrade_stamina(
+    _: Trigger<Pointer<Click>>,
+    player: Single<(&mut MovementStats, &mut Stamina), With<Player>>,
+    mut commands: Commands,
+) {
+    let (mut movement_stats, mut stamina) = player.into_inner();
+    movement_stats.max_stamina += 25.0;
+    movement_stats.stamina_regen += 5.0;
+    stamina.current = movement_stats.max_stamina;
+    commands.trigger(DespawnUpgrades);
+}
+
+fn upgrade_air_dash(
+    _: Trigger<Pointer<Click>>,
+    mut movement_stats: Single<&mut MovementStats, With<Player>>,
+    mut commands: Commands,
+) {
+    movement_stats.dash_distance += 1.5;
+    movement_stats.dash_cooldown_secs = (movement_stats.dash_cooldown_secs - 0.25).max(0.5);
+    commands.trigger(DespawnUpgrades);
+}
+
 fn upgrade_accuracy(
     _: Trigger<Pointer<Click>>,
     mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
@@ -202,6 +272,17 @@ fn upgrade_jump_shot_pushback(
     commands.trigger(DespawnUpgrades);
 }
 
+fn upgrade_blast_jump(
+    _: Trigger<Pointer<Click>>,
+    mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
+    mut commands: Commands,
+) {
+    // Your own blasts push you further and hurt you less, until they don't hurt at all.
+    weapon_stats.blast_jump += 0.5;
+    weapon_stats.self_blast_damage = (weapon_stats.self_blast_damage - 0.5).max(0.0);
+    commands.trigger(DespawnUpgrades);
+}
+
 fn upgrade_enemy_explosion_radius(
     _: Trigger<Pointer<Click>>,
     mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
@@ -211,6 +292,57 @@ fn upgrade_enemy_explosion_radius(
     commands.trigger(DespawnUpgrades);
 }
 
+fn upgrade_incendiary_shells(
+    _: Trigger<Pointer<Click>>,
+    mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
+    mut commands: Commands,
+) {
+    // Taking the upgrade again makes the fire last longer.
+    let duration_secs = weapon_stats
+        .status_effect
+        .filter(|effect| effect.kind == StatusEffectKind::Burning)
+        .map_or(2.0, |effect| effect.duration_secs + 1.0);
+    weapon_stats.status_effect = Some(StatusEffectSpec::new(
+        StatusEffectKind::Burning,
+        duration_secs,
+    ));
+    commands.trigger(DespawnUpgrades);
+}
+
+fn upgrade_armor(
+    _: Trigger<Pointer<Click>>,
+    mut armor: Single<&mut Armor, With<Player>>,
+    mut commands: Commands,
+) {
+    armor.max += 25.0;
+    armor.current = armor.max;
+    commands.trigger(DespawnUpgrades);
+}
+
+fn upgrade_shield(
+    _: Trigger<Pointer<Click>>,
+    mut shield: Single<&mut Shield, With<Player>>,
+    mut commands: Commands,
+) {
+    shield.max += 15.0;
+    shield.recharge_per_second += 2.5;
+    let delay = (shield.recharge_delay.duration().as_secs_f32() - 0.25).max(1.0);
+    shield
+        .recharge_delay
+        .set_duration(Duration::from_secs_f32(delay));
+    commands.trigger(DespawnUpgrades);
+}
+
+fn upgrade_regeneration(
+    _: Trigger<Pointer<Click>>,
+    mut regeneration: Single<&mut Regeneration, With<Player>>,
+    mut commands: Commands,
+) {
+    regeneration.per_second += 0.75;
+    regeneration.max_fraction = (regeneration.max_fraction + 0.1).min(1.0);
+    commands.trigger(DespawnUpgrades);
+}
+
 fn unoffer_upgrades(_trigger: Trigger<WaveFinishedPreparing>, mut commands: Commands) {
     commands.trigger(DespawnUpgrades);
 }
@@ -9,7 +9,14 @@ use crate::{
     PrePhysicsAppSystems,
     gameplay::{
         hud::WaveIconParent,
-        npc::{Npc, stats::NpcStats},
+        npc::{
+            Npc,
+            attack::{AttackDefinition, AttackSet},
+            kamikaze::Kamikaze,
+            ranged::Ranged,
+            stats::NpcStats,
+        },
+        pickups::{DropTable, PickupKind},
     },
     props::generic::BarrelLargeClosed,
     third_party::avian3d::CollisionLayer,
@@ -229,6 +236,7 @@ impl Default for SpawnPackets {
                     (Millis(700), SpawnVariant::BasicEnemy),
                     (Millis(150), SpawnVariant::BasicEnemy),
                     (Millis(250), SpawnVariant::BasicEnemy),
+                    (Millis(800), SpawnVariant::Spitter),
                 ]
                 .into(),
             ),
@@ -245,6 +253,7 @@ impl Default for SpawnPackets {
                     (Millis(500), SpawnVariant::ExplosiveBarrel),
                     (Millis(600), SpawnVariant::SmallEnemy),
                     (Millis(700), SpawnVariant::BasicEnemy),
+                    (Millis(900), SpawnVariant::Kamikaze),
                 ]
                 .into(),
             ),
@@ -267,6 +276,7 @@ impl Default for SpawnPackets {
                     (Millis(1000), SpawnVariant::BasicEnemy),
                     (Millis(1100), SpawnVariant::BasicEnemy),
                     (Millis(1200), SpawnVariant::BasicEnemy),
+                    (Millis(1300), SpawnVariant::Spitter),
                 ]
                 .into(),
             ),
@@ -286,6 +296,7 @@ impl Default for SpawnPackets {
                     (Millis(800), SpawnVariant::ExplosiveBarrel),
                     (Millis(850), SpawnVariant::ExplosiveBarrel),
                     (Millis(900), SpawnVariant::BasicEnemy),
+                    (Millis(1000), SpawnVariant::Kamikaze),
                 ]
                 .into(),
             ),
@@ -305,6 +316,8 @@ impl Default for SpawnPackets {
                     (Millis(800), SpawnVariant::ExplosiveBarrel),
                     (Millis(850), SpawnVariant::ExplosiveBarrel),
                     (Millis(900), SpawnVariant::BasicEnemy),
+                    (Millis(1000), SpawnVariant::Spitter),
+                    (Millis(1100), SpawnVariant::Spitter),
                 ]
                 .into(),
             ),
@@ -350,6 +363,8 @@ impl Default for SpawnPackets {
                     (Millis(1500), SpawnVariant::BasicEnemy),
                     (Millis(1600), SpawnVariant::BasicEnemy),
                     (Millis(1700), SpawnVariant::BasicEnemy),
+                    (Millis(1800), SpawnVariant::Kamikaze),
+                    (Millis(1900), SpawnVariant::Kamikaze),
                 ]
                 .into(),
             ),
@@ -461,6 +476,8 @@ impl Default for SpawnPackets {
                     (Millis(6000), SpawnVariant::BasicEnemy),
                     (Millis(6200), SpawnVariant::BasicEnemy),
                     (Millis(6400), SpawnVariant::BasicEnemy),
+                    (Millis(6600), SpawnVariant::Kamikaze),
+                    (Millis(6800), SpawnVariant::Kamikaze),
                 ]
                 .into(),
             ),
@@ -597,6 +614,12 @@ fn advance_waves(
                             stagger_duration: (0.2 * (1.0 - (buff_i as f32 * 0.05).min(0.5)))
                                 ..(0.4 * (1.0 - (buff_i as f32 * 0.05).min(0.5))),
                         },
+                        AttackSet(vec![AttackDefinition::SWIPE, AttackDefinition::LUNGE]),
+                        DropTable(vec![
+                            (PickupKind::Health(10.0), 0.1),
+                            (PickupKind::Armor(10.0), 0.05),
+                            (PickupKind::Ammo(6), 0.25),
+                        ]),
                     ));
                 }
                 SpawnVariant::BigEnemy => {
@@ -614,6 +637,12 @@ fn advance_waves(
                             stagger_duration: (0.1 * (1.0 - (buff_i as f32 * 0.05).min(0.5)))
                                 ..(0.3 * (1.0 - (buff_i as f32 * 0.05).min(0.5))),
                         },
+                        AttackSet(vec![AttackDefinition::SWIPE, AttackDefinition::SLAM]),
+                        DropTable(vec![
+                            (PickupKind::Health(30.0), 0.5),
+                            (PickupKind::Armor(25.0), 0.25),
+                            (PickupKind::Ammo(12), 0.25),
+                        ]),
                     ));
                 }
                 SpawnVariant::SmallEnemy => {
@@ -631,6 +660,60 @@ fn advance_waves(
                             stagger_duration: (0.2 * (1.0 - (buff_i as f32 * 0.05).min(0.5)))
                                 ..(0.3 * (1.0 - (buff_i as f32 * 0.05).min(0.5))),
                         },
+                        AttackSet(vec![AttackDefinition::SWIPE, AttackDefinition::LUNGE]),
+                        DropTable(vec![
+                            (PickupKind::Health(5.0), 0.05),
+                            (PickupKind::Ammo(4), 0.15),
+                        ]),
+                    ));
+                }
+                SpawnVariant::Spitter => {
+                    spawn_commands.insert((
+                        Name::new("Spitter"),
+                        Npc,
+                        NpcStats {
+                            health: scale_stat(60.0, 0.1),
+                            desired_speed: scale_stat(6.0, 0.1),
+                            max_speed: scale_stat(6.0, 0.1),
+                            attack_damage: scale_stat(12.0, 0.05),
+                            attack_speed_range: scale_stat(1.5, 0.1)..scale_stat(2.3, 0.1),
+                            size: 0.8,
+                            stagger_chance: 0.5,
+                            stagger_duration: (0.3 * (1.0 - (buff_i as f32 * 0.05).min(0.5)))
+                                ..(0.5 * (1.0 - (buff_i as f32 * 0.05).min(0.5))),
+                        },
+                        Ranged {
+                            projectile_damage: scale_stat(12.0, 0.05),
+                            ..default()
+                        },
+                        DropTable(vec![
+                            (PickupKind::Health(10.0), 0.15),
+                            (PickupKind::Armor(10.0), 0.1),
+                            (PickupKind::Ammo(6), 0.25),
+                        ]),
+                    ));
+                }
+                SpawnVariant::Kamikaze => {
+                    spawn_commands.insert((
+                        Name::new("Kamikaze"),
+                        Npc,
+                        NpcStats {
+                            health: scale_stat(20.0, 0.1),
+                            desired_speed: scale_stat(13.0, 0.1),
+                            max_speed: scale_stat(14.0, 0.1),
+                            // Kamikazes explode instead of attacking.
+                            attack_damage: 0.0,
+                            attack_speed_range: 1.5..2.3,
+                            size: 0.9,
+                            // Nothing stops a kamikaze.
+                            stagger_chance: 0.0,
+                            stagger_duration: 0.1..0.2,
+                        },
+                        Kamikaze::default(),
+                        DropTable(vec![
+                            (PickupKind::Health(5.0), 0.05),
+                            (PickupKind::Ammo(4), 0.15),
+                        ]),
                     ));
                 }
                 SpawnVariant::ExplosiveBarrel => {
@@ -641,7 +724,7 @@ fn advance_waves(
     }
 }
 
-#[derive(Component, Reflect)]
+#[derive(Component, Reflect, Clone)]
 #[reflect(Component)]
 pub(crate) struct Waves {
     waves: Vec<Wave>,
@@ -765,17 +848,35 @@ impl Waves {
         self.wave_stopwatch.reset();
     }
 
-    fn is_finished(&self) -> bool {
+    pub(crate) fn is_finished(&self) -> bool {
         self.current_wave >= self.waves.len() && self.current_packets.is_empty()
     }
+
+    /// A copy of these waves that resumes right before the current wave, including its preparation time.
+    ///
+    /// The copy is rewound to the moment the previous wave was cleared, so that
+    /// restoring it advances into the current wave and triggers all the usual wave events again.
+    pub(crate) fn checkpoint(&self) -> Self {
+        let mut checkpoint = self.clone();
+        checkpoint.current_wave = self.current_wave.saturating_sub(1);
+        checkpoint.current_packets.clear();
+        if let Some(wave) = checkpoint.current_wave_mut() {
+            wave.packet_kinds.clear();
+        }
+        checkpoint.wave_stopwatch.reset();
+        let mut prep_timer = Timer::new(Duration::ZERO, TimerMode::Once);
+        prep_timer.tick(Duration::ZERO);
+        checkpoint.prep_timer = prep_timer;
+        checkpoint
+    }
 }
 
 #[derive(PointClass, Component, Debug, Reflect)]
 #[reflect(QuakeClass, Component)]
 #[base(Transform, Visibility)]
 #[model("models/gizmo/spawner.gltf")]
-struct Spawner {
-    radius: f32,
+pub(crate) struct Spawner {
+    pub(crate) radius: f32,
 }
 
 impl Default for Spawner {
@@ -892,5 +993,7 @@ enum SpawnVariant {
     BasicEnemy,
     BigEnemy,
     SmallEnemy,
+    Spitter,
+    Kamikaze,
     ExplosiveBarrel,
 }
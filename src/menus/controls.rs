@@ -0,0 +1,283 @@
+//! The controls screen accessible from the settings.
+//! Lists every action and lets the player rebind it by pressing the new input.
+
+use bevy::{
+    ecs::spawn::SpawnWith, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    font::FontAssets,
+    gameplay::player::bindings::{Bindings, BoundInput, Control, ReservedInput},
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(BindingButton, BindingStatus)>();
+    app.add_systems(OnEnter(Menu::Controls), spawn_controls_menu);
+    app.add_systems(OnExit(Menu::Controls), (stop_capture, save_bindings));
+    app.add_systems(
+        Update,
+        (
+            capture_binding.run_if(resource_exists::<Capture>),
+            update_binding_labels,
+            // While capturing, Escape cancels the capture instead.
+            go_back
+                .run_if(input_just_pressed(KeyCode::Escape).and(not(resource_exists::<Capture>))),
+        )
+            .run_if(in_state(Menu::Controls)),
+    );
+}
+
+/// Which of a control's inputs a [`BindingButton`] shows and rebinds.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+enum BindingSlot {
+    KeyboardMouse,
+    Gamepad,
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+struct BindingButton {
+    control: Control,
+    slot: BindingSlot,
+}
+
+impl BindingButton {
+    fn label(self, bindings: &Bindings) -> String {
+        let binding = bindings.get(self.control);
+        match self.slot {
+            BindingSlot::KeyboardMouse => binding.keyboard.label(),
+            BindingSlot::Gamepad => binding.gamepad.map_or("Unbound".to_string(), |button| {
+                BoundInput::Gamepad(button).label()
+            }),
+        }
+    }
+}
+
+/// Tells the player what is going on while rebinding.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct BindingStatus;
+
+/// The binding that is waiting for the player to press an input.
+#[derive(Resource, Debug)]
+struct Capture(BindingButton);
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn spawn_controls_menu(mut commands: Commands, fonts: Res<FontAssets>) {
+    let font = fonts.default.clone();
+    commands.spawn((
+        widget::ui_root("Controls Screen"),
+        StateScoped(Menu::Controls),
+        GlobalZIndex(2),
+        children![
+            widget::header("Controls", fonts.default.clone()),
+            (
+                Name::new("Controls Grid"),
+                Node {
+                    display: Display::Grid,
+                    row_gap: Px(6.0),
+                    column_gap: Px(20.0),
+                    grid_template_columns: RepeatedGridTrack::px(3, 240.0),
+                    ..default()
+                },
+                Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+                    parent.spawn(Node::default());
+                    parent.spawn(widget::label("Keyboard & Mouse", font.clone()));
+                    parent.spawn(widget::label("Gamepad", font.clone()));
+                    for control in Control::ALL {
+                        parent.spawn((
+                            widget::label(control.name(), font.clone()),
+                            Node {
+                                justify_self: JustifySelf::End,
+                                ..default()
+                            },
+                        ));
+                        parent.spawn((
+                            widget::button_medium("", font.clone(), start_capture),
+                            BindingButton {
+                                control,
+                                slot: BindingSlot::KeyboardMouse,
+                            },
+                        ));
+                        if control.has_gamepad_binding() {
+                            parent.spawn((
+                                widget::button_medium("", font.clone(), start_capture),
+                                BindingButton {
+                                    control,
+                                    slot: BindingSlot::Gamepad,
+                                },
+                            ));
+                        } else {
+                            parent.spawn(widget::label("Left Stick", font.clone()));
+                        }
+                    }
+                    // Looking around is an axis, so it can't be rebound to a button.
+                    parent.spawn((
+                        widget::label("Look Around", font.clone()),
+                        Node {
+                            justify_self: JustifySelf::End,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn(widget::label("Mouse", font.clone()));
+                    parent.spawn(widget::label("Right Stick", font.clone()));
+                })),
+            ),
+            (widget::label("", fonts.default.clone()), BindingStatus),
+            (
+                Name::new("Controls Buttons"),
+                Node {
+                    column_gap: Px(20.0),
+                    ..default()
+                },
+                children![
+                    widget::button("Reset to Defaults", fonts.default.clone(), reset_bindings),
+                    widget::button("Back", fonts.default.clone(), go_back_on_click),
+                ],
+            ),
+        ],
+    ));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn start_capture(
+    trigger: Trigger<Pointer<Click>>,
+    child_of: Query<&ChildOf>,
+    buttons: Query<&BindingButton>,
+    mut status: Single<&mut Text, With<BindingStatus>>,
+    mut commands: Commands,
+) {
+    // The click lands on the inner button, but the binding is stored on the outer one.
+    let Some(button) = child_of
+        .iter_ancestors(trigger.target())
+        .find_map(|ancestor| buttons.get(ancestor).ok())
+    else {
+        return;
+    };
+    commands.insert_resource(Capture(*button));
+    status.0 = match button.slot {
+        BindingSlot::KeyboardMouse => "Press a key or mouse button, or Escape to cancel.",
+        BindingSlot::Gamepad => "Press a gamepad button, or Escape to cancel.",
+    }
+    .to_string();
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn capture_binding(
+    capture: Res<Capture>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut bindings: ResMut<Bindings>,
+    mut status: Single<&mut Text, With<BindingStatus>>,
+    mut commands: Commands,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        commands.remove_resource::<Capture>();
+        status.0 = String::new();
+        return;
+    }
+    let BindingButton { control, slot } = capture.0;
+    let input = match slot {
+        BindingSlot::KeyboardMouse => keys
+            .get_just_pressed()
+            .map(|key| BoundInput::Key(*key))
+            .chain(
+                mouse_buttons
+                    .get_just_pressed()
+                    .map(|button| BoundInput::Mouse(*button)),
+            )
+            .find(|input| input.is_bindable()),
+        BindingSlot::Gamepad => gamepads
+            .iter()
+            .flat_map(|gamepad| gamepad.get_just_pressed())
+            .map(|button| BoundInput::Gamepad(*button))
+            .find(|input| input.is_bindable()),
+    };
+    let Some(input) = input else {
+        return;
+    };
+
+    let conflict = match input {
+        BoundInput::Gamepad(button) => bindings.bind_gamepad(control, button),
+        input => match bindings.bind_keyboard(control, input) {
+            Ok(conflict) => conflict,
+            // Keep capturing so that the player can pick another key.
+            Err(ReservedInput(input)) => {
+                status.0 = format!(
+                    "{} pauses the game and can't be bound. Press another key, or Escape to cancel.",
+                    input.label()
+                );
+                return;
+            }
+        },
+    };
+    status.0 = conflict.map_or(String::new(), |other| {
+        format!(
+            "{} was already bound to {}, so the two swapped.",
+            input.label(),
+            other.name()
+        )
+    });
+    commands.remove_resource::<Capture>();
+}
+
+fn update_binding_labels(
+    buttons: Query<(Entity, &BindingButton)>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
+    bindings: Res<Bindings>,
+    capture: Option<Res<Capture>>,
+) {
+    for (entity, button) in &buttons {
+        let label = if capture.as_ref().is_some_and(|capture| capture.0 == *button) {
+            "...".to_string()
+        } else {
+            button.label(&bindings)
+        };
+        let Some(text_entity) = children
+            .iter_descendants(entity)
+            .find(|child| texts.contains(*child))
+        else {
+            continue;
+        };
+        let Ok(mut text) = texts.get_mut(text_entity) else {
+            continue;
+        };
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn reset_bindings(
+    _trigger: Trigger<Pointer<Click>>,
+    mut bindings: ResMut<Bindings>,
+    mut status: Single<&mut Text, With<BindingStatus>>,
+) {
+    bindings.reset();
+    status.0 = "Restored the default bindings.".to_string();
+}
+
+fn stop_capture(mut commands: Commands) {
+    commands.remove_resource::<Capture>();
+}
+
+fn save_bindings(bindings: Res<Bindings>) {
+    bindings.save();
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn go_back_on_click(_trigger: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
@@ -6,8 +6,8 @@ use crate::{
     font::FontAssets,
     gameplay::{
         crosshair::CrosshairState,
-        health::OnDeath,
-        player::{Player, default_input::BlocksInput},
+        player::default_input::BlocksInput,
+        respawn::{Checkpoint, GameOver},
         time::GameplayTime,
     },
     screens::Screen,
@@ -15,16 +15,16 @@ use crate::{
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_observer(on_player_death);
+    app.add_observer(on_game_over);
 }
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub(crate) struct GameOverMenu;
 
-fn on_player_death(
-    trigger: Trigger<OnDeath>,
-    player: Query<(), With<Player>>,
+fn on_game_over(
+    _trigger: Trigger<GameOver>,
+    checkpoint: Option<Res<Checkpoint>>,
     mut crosshair: Single<&mut CrosshairState>,
     mut block_input: ResMut<BlocksInput>,
     fonts: Res<FontAssets>,
@@ -32,15 +32,12 @@ fn on_player_death(
     mut commands: Commands,
     mut window: Single<&mut Window>,
 ) {
-    if !player.contains(trigger.target()) {
-        return;
-    }
     window.cursor_options.visible = true;
     let elapsed_secs = gameplay_time.elapsed_secs();
     let minutes = (elapsed_secs / 60.0) as u32;
     let seconds = (elapsed_secs % 60.0) as u32;
     let milliseconds = (elapsed_secs * 1000.0) as u32 % 1000;
-    commands.spawn((
+    let mut menu = commands.spawn((
         widget::ui_root("Game Over Menu"),
         StateScoped(Screen::Gameplay),
         GameOverMenu,
@@ -50,14 +47,33 @@ fn on_player_death(
                 format!("Time: {minutes:02}:{seconds:02}.{milliseconds:03}"),
                 fonts.default.clone()
             ),
-            widget::button("Try Again", fonts.default.clone(), try_again),
-            widget::button("Quit to Title", fonts.default.clone(), quit_to_title),
         ],
     ));
-    crosshair
-        .wants_free_cursor
-        .insert(on_player_death.type_id());
-    block_input.insert(on_player_death.type_id());
+    if let Some(checkpoint) = checkpoint {
+        menu.with_child(widget::button(
+            format!("Try Again from Wave {}", checkpoint.wave),
+            fonts.default.clone(),
+            try_again,
+        ));
+        menu.with_child(widget::button(
+            "Start Over",
+            fonts.default.clone(),
+            start_over,
+        ));
+    } else {
+        menu.with_child(widget::button(
+            "Try Again",
+            fonts.default.clone(),
+            try_again,
+        ));
+    }
+    menu.with_child(widget::button(
+        "Quit to Title",
+        fonts.default.clone(),
+        quit_to_title,
+    ));
+    crosshair.wants_free_cursor.insert(on_game_over.type_id());
+    block_input.insert(on_game_over.type_id());
 }
 
 fn try_again(
@@ -67,10 +83,22 @@ fn try_again(
     mut block_input: ResMut<BlocksInput>,
 ) {
     next_screen.set(Screen::Loading);
-    crosshair
-        .wants_free_cursor
-        .remove(&on_player_death.type_id());
-    block_input.remove(&on_player_death.type_id());
+    crosshair.wants_free_cursor.remove(&on_game_over.type_id());
+    block_input.remove(&on_game_over.type_id());
+}
+
+/// Like [`try_again`], but throws away the checkpoint to start at the first wave.
+fn start_over(
+    _trigger: Trigger<Pointer<Click>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut crosshair: Single<&mut CrosshairState>,
+    mut block_input: ResMut<BlocksInput>,
+    mut commands: Commands,
+) {
+    commands.remove_resource::<Checkpoint>();
+    next_screen.set(Screen::Loading);
+    crosshair.wants_free_cursor.remove(&on_game_over.type_id());
+    block_input.remove(&on_game_over.type_id());
 }
 
 fn quit_to_title(
@@ -80,8 +108,6 @@ fn quit_to_title(
     mut block_input: ResMut<BlocksInput>,
 ) {
     next_screen.set(Screen::Title);
-    crosshair
-        .wants_free_cursor
-        .remove(&on_player_death.type_id());
-    block_input.remove(&on_player_death.type_id());
+    crosshair.wants_free_cursor.remove(&on_game_over.type_id());
+    block_input.remove(&on_game_over.type_id());
 }
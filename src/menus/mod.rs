@@ -1,6 +1,7 @@
 //! The game's main screen states and transitions between them.
 
 mod assets;
+mod controls;
 mod credits;
 pub(crate) mod game_over;
 pub(crate) mod game_won;
@@ -16,6 +17,7 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_plugins((
         assets::plugin,
+        controls::plugin,
         credits::plugin,
         main::plugin,
         settings::plugin,
@@ -35,5 +37,6 @@ pub(crate) enum Menu {
     Main,
     Credits,
     Settings,
+    Controls,
     Pause,
 }
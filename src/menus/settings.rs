@@ -15,8 +15,10 @@ use crate::{
     audio::{DEFAULT_VOLUME, max_volume},
     font::FontAssets,
     gameplay::{
-        gore_settings::{Gore, GoreSettings},
+        damage_feedback::DamageFeedbackSettings,
+        gore_settings::{DeathStyle, Gore, GoreSettings},
         player::camera::{CameraSensitivity, MouseInversion, WorldModelFov},
+        respawn::RespawnSettings,
     },
     menus::Menu,
     screens::Screen,
@@ -40,6 +42,8 @@ pub(super) fn plugin(app: &mut App) {
             update_camera_sensitivity_label,
             update_camera_fov_label,
             update_gib_count_label,
+            update_ragdoll_count_label,
+            update_damage_feedback_label,
         )
             .run_if(in_state(Menu::Settings)),
     );
@@ -51,11 +55,13 @@ fn spawn_settings_menu(
     fonts: Res<FontAssets>,
     gore_settings: Res<GoreSettings>,
     mouse_inversion: Res<MouseInversion>,
+    respawn_settings: Res<RespawnSettings>,
 ) {
     let fonts_outer = fonts.clone();
     let fonts = fonts.clone();
     let gore_settings = gore_settings.clone();
     let mouse_inversion = mouse_inversion.clone();
+    let respawn_settings = respawn_settings.clone();
     commands.spawn((
         widget::ui_root("Settings Screen"),
         StateScoped(Menu::Settings),
@@ -137,6 +143,21 @@ fn spawn_settings_menu(
                         fonts.default.clone(),
                         fonts.default.clone(),
                     ));
+                    // Damage feedback strength
+                    parent.spawn((
+                        widget::label("Damage Effects", fonts.default.clone()),
+                        Node {
+                            justify_self: JustifySelf::End,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn(widget::plus_minus_bar(
+                        DamageFeedbackLabel,
+                        lower_damage_feedback,
+                        raise_damage_feedback,
+                        fonts.default.clone(),
+                        fonts.default.clone(),
+                    ));
                     // Gib count
                     parent.spawn((
                         widget::label("Number of body parts", fonts.default.clone()),
@@ -185,6 +206,45 @@ fn spawn_settings_menu(
                             };
                         },
                     ));
+                    // What dead NPCs leave behind
+                    parent.spawn((
+                        widget::label("Death Style", fonts.default.clone()),
+                        Node {
+                            justify_self: JustifySelf::End,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn(widget::cycle_select(
+                        vec!["Gibs".to_string(), "Ragdolls".to_string()],
+                        match gore_settings.death_style {
+                            DeathStyle::Gibs => 0,
+                            DeathStyle::Ragdoll => 1,
+                        },
+                        fonts.default.clone(),
+                        |trigger: Trigger<OnChangeSelection>,
+                         mut gore_settings: ResMut<GoreSettings>| {
+                            let selection = trigger.selection;
+                            gore_settings.death_style = match selection {
+                                0 => DeathStyle::Gibs,
+                                _ => DeathStyle::Ragdoll,
+                            };
+                        },
+                    ));
+                    // Ragdoll count
+                    parent.spawn((
+                        widget::label("Number of ragdolls", fonts.default.clone()),
+                        Node {
+                            justify_self: JustifySelf::End,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn(widget::plus_minus_bar(
+                        RagdollCountLabel,
+                        lower_ragdoll_count,
+                        raise_ragdoll_count,
+                        fonts.default.clone(),
+                        fonts.default.clone(),
+                    ));
                     // Gore settings for blood decals
                     parent.spawn((
                         widget::label("Blood Splatter", fonts.default.clone()),
@@ -218,9 +278,78 @@ fn spawn_settings_menu(
                             };
                         },
                     ));
+                    // Lives
+                    parent.spawn((
+                        widget::label("Lives", fonts.default.clone()),
+                        Node {
+                            justify_self: JustifySelf::End,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn(widget::cycle_select(
+                        vec!["Off".to_string(), "3".to_string(), "5".to_string()],
+                        match respawn_settings.lives {
+                            None => 0,
+                            Some(..=3) => 1,
+                            Some(_) => 2,
+                        },
+                        fonts.default.clone(),
+                        |trigger: Trigger<OnChangeSelection>,
+                         mut respawn_settings: ResMut<RespawnSettings>| {
+                            let selection = trigger.selection;
+                            respawn_settings.lives = match selection {
+                                0 => None,
+                                1 => Some(3),
+                                _ => Some(5),
+                            };
+                        },
+                    ));
+                    // Checkpoints
+                    parent.spawn((
+                        widget::label("Checkpoints", fonts.default.clone()),
+                        Node {
+                            justify_self: JustifySelf::End,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn(widget::cycle_select(
+                        vec![
+                            "Off".to_string(),
+                            "Every wave".to_string(),
+                            "Every 3 waves".to_string(),
+                            "Every 5 waves".to_string(),
+                        ],
+                        match respawn_settings.checkpoint_every {
+                            None => 0,
+                            Some(..=1) => 1,
+                            Some(2..=3) => 2,
+                            Some(_) => 3,
+                        },
+                        fonts.default.clone(),
+                        |trigger: Trigger<OnChangeSelection>,
+                         mut respawn_settings: ResMut<RespawnSettings>| {
+                            let selection = trigger.selection;
+                            respawn_settings.checkpoint_every = match selection {
+                                0 => None,
+                                1 => Some(1),
+                                2 => Some(3),
+                                _ => Some(5),
+                            };
+                        },
+                    ));
                 })),
             ),
-            widget::button("Back", fonts_outer.default.clone(), go_back_on_click),
+            (
+                Name::new("Settings Buttons"),
+                Node {
+                    column_gap: Px(20.0),
+                    ..default()
+                },
+                children![
+                    widget::button("Controls", fonts_outer.default.clone(), open_controls_menu),
+                    widget::button("Back", fonts_outer.default.clone(), go_back_on_click),
+                ],
+            ),
         ],
     ));
 }
@@ -382,6 +511,59 @@ fn update_gib_count_label(
     label.0 = format!("{}", gore_settings.gib_count);
 }
 
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct RagdollCountLabel;
+
+fn lower_ragdoll_count(_trigger: Trigger<Pointer<Click>>, mut gore_settings: ResMut<GoreSettings>) {
+    gore_settings.ragdoll_count = gore_settings.ragdoll_count.saturating_sub(1);
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn raise_ragdoll_count(_trigger: Trigger<Pointer<Click>>, mut gore_settings: ResMut<GoreSettings>) {
+    gore_settings.ragdoll_count += 1;
+    gore_settings.ragdoll_count = gore_settings.ragdoll_count.min(20);
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_ragdoll_count_label(
+    mut label: Single<&mut Text, With<RagdollCountLabel>>,
+    gore_settings: Res<GoreSettings>,
+) {
+    label.0 = format!("{}", gore_settings.ragdoll_count);
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct DamageFeedbackLabel;
+
+fn lower_damage_feedback(
+    _trigger: Trigger<Pointer<Click>>,
+    mut settings: ResMut<DamageFeedbackSettings>,
+) {
+    settings.strength = (settings.strength - 0.25).max(0.0);
+}
+
+fn raise_damage_feedback(
+    _trigger: Trigger<Pointer<Click>>,
+    mut settings: ResMut<DamageFeedbackSettings>,
+) {
+    settings.strength = (settings.strength + 0.25).min(1.0);
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_damage_feedback_label(
+    mut label: Single<&mut Text, With<DamageFeedbackLabel>>,
+    settings: Res<DamageFeedbackSettings>,
+) {
+    label.0 = format!("{:.0}%", settings.strength * 100.0);
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn open_controls_menu(_trigger: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Controls);
+}
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn go_back_on_click(
     _trigger: Trigger<Pointer<Click>>,
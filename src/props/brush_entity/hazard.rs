@@ -0,0 +1,214 @@
+//! Map-authored hazards: `trigger_hurt` volumes that deal damage over time,
+//! and `trigger_kill` volumes that kill instantly.
+
+use avian3d::prelude::*;
+use bevy::{platform::collections::HashSet, prelude::*};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use bevy_trenchbroom::prelude::*;
+
+use crate::{
+    PostPhysicsAppSystems,
+    despawn_after::Despawn,
+    gameplay::{
+        health::{DamageKind, Health, OnDamage, OnDeath},
+        npc::Npc,
+        player::Player,
+    },
+    screens::Screen,
+    third_party::avian3d::CollisionLayer,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(TriggerHurt, TriggerKill, HazardFilter, HurtTick)>();
+    app.add_observer(setup_hazard::<TriggerHurt>);
+    app.add_observer(setup_hazard::<TriggerKill>);
+    app.add_systems(
+        Update,
+        (hurt_inside_volumes, kill_inside_volumes)
+            .in_set(PostPhysicsAppSystems::TriggerDeath)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How often `trigger_hurt` volumes deal damage.
+const HURT_TICK_SECS: f32 = 0.5;
+
+/// Which entities a hazard affects.
+#[derive(FgdType, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub(crate) enum HazardFilter {
+    #[default]
+    Everything,
+    PlayerOnly,
+    NpcsOnly,
+    /// Everything that is neither the player nor an NPC.
+    PropsOnly,
+}
+
+impl HazardFilter {
+    fn allows(self, is_player: bool, is_npc: bool) -> bool {
+        match self {
+            HazardFilter::Everything => true,
+            HazardFilter::PlayerOnly => is_player,
+            HazardFilter::NpcsOnly => is_npc,
+            HazardFilter::PropsOnly => !is_player && !is_npc,
+        }
+    }
+}
+
+/// A volume that damages everything inside it over time.
+#[derive(SolidClass, Component, Debug, Clone, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+#[spawn_hooks(SpawnHooks::new().convex_collider())]
+#[require(HurtTick)]
+pub(crate) struct TriggerHurt {
+    pub(crate) damage_per_second: f32,
+    pub(crate) damage_kind: DamageKind,
+    pub(crate) filter: HazardFilter,
+}
+
+impl Default for TriggerHurt {
+    fn default() -> Self {
+        Self {
+            damage_per_second: 20.0,
+            damage_kind: DamageKind::Generic,
+            filter: HazardFilter::Everything,
+        }
+    }
+}
+
+/// A volume that instantly kills everything inside it.
+/// Props without health are despawned.
+#[derive(SolidClass, Component, Debug, Clone, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+#[spawn_hooks(SpawnHooks::new().convex_collider())]
+pub(crate) struct TriggerKill {
+    pub(crate) damage_kind: DamageKind,
+    pub(crate) filter: HazardFilter,
+}
+
+impl Default for TriggerKill {
+    fn default() -> Self {
+        Self {
+            damage_kind: DamageKind::OutOfBounds,
+            filter: HazardFilter::Everything,
+        }
+    }
+}
+
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect(Component)]
+struct HurtTick(Timer);
+
+impl Default for HurtTick {
+    fn default() -> Self {
+        Self(Timer::from_seconds(HURT_TICK_SECS, TimerMode::Repeating))
+    }
+}
+
+/// Turn the brush into an invisible sensor that keeps track of what is inside it.
+fn setup_hazard<T: Component>(trigger: Trigger<OnAdd, T>, mut commands: Commands) {
+    commands.entity(trigger.target()).insert((
+        Sensor,
+        CollidingEntities::default(),
+        CollisionLayers::new(
+            CollisionLayer::Sensor,
+            [
+                CollisionLayer::Player,
+                CollisionLayer::Npc,
+                CollisionLayer::Prop,
+                CollisionLayer::Character,
+            ],
+        ),
+        Visibility::Hidden,
+    ));
+}
+
+/// A body inside a hazard volume.
+struct BodyInside {
+    entity: Entity,
+    is_player: bool,
+    is_npc: bool,
+}
+
+/// Collects the bodies inside a volume that pass its filter.
+fn bodies_inside(
+    colliding_entities: &CollidingEntities,
+    filter: HazardFilter,
+    collider_of: &Query<&ColliderOf>,
+    kinds: &Query<(Has<Player>, Has<Npc>)>,
+) -> Vec<BodyInside> {
+    // Bodies can have multiple colliders inside the volume.
+    let bodies = colliding_entities
+        .iter()
+        .filter_map(|collider| collider_of.get(*collider).ok().map(|c| c.body))
+        .collect::<HashSet<_>>();
+    bodies
+        .into_iter()
+        .filter_map(|entity| {
+            let (is_player, is_npc) = kinds.get(entity).ok()?;
+            filter.allows(is_player, is_npc).then_some(BodyInside {
+                entity,
+                is_player,
+                is_npc,
+            })
+        })
+        .collect()
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn hurt_inside_volumes(
+    mut volumes: Query<(&TriggerHurt, &CollidingEntities, &mut HurtTick)>,
+    collider_of: Query<&ColliderOf>,
+    kinds: Query<(Has<Player>, Has<Npc>)>,
+    damageable: Query<(), With<Health>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (hurt, colliding_entities, mut tick) in &mut volumes {
+        tick.tick(time.delta());
+        let ticks = tick.times_finished_this_tick();
+        if ticks == 0 {
+            continue;
+        }
+        let damage = hurt.damage_per_second * HURT_TICK_SECS * ticks as f32;
+        for body in bodies_inside(colliding_entities, hurt.filter, &collider_of, &kinds) {
+            if damageable.contains(body.entity) {
+                commands
+                    .entity(body.entity)
+                    .trigger(OnDamage::new(damage, hurt.damage_kind));
+            }
+        }
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn kill_inside_volumes(
+    volumes: Query<(&TriggerKill, &CollidingEntities)>,
+    collider_of: Query<&ColliderOf>,
+    kinds: Query<(Has<Player>, Has<Npc>)>,
+    damageable: Query<(), With<Health>>,
+    mut commands: Commands,
+) {
+    // Volumes can overlap, so make sure every body is only killed once.
+    let mut killed = HashSet::new();
+    for (kill, colliding_entities) in &volumes {
+        for body in bodies_inside(colliding_entities, kill.filter, &collider_of, &kinds) {
+            if !killed.insert(body.entity) {
+                continue;
+            }
+            if damageable.contains(body.entity) {
+                // Removing the health makes sure we only trigger the death once.
+                commands
+                    .entity(body.entity)
+                    .remove::<Health>()
+                    .trigger(OnDeath::new(kill.damage_kind));
+            } else if !body.is_player && !body.is_npc {
+                // Dead characters are cleaned up by their death handlers.
+                commands.entity(body.entity).try_insert(Despawn);
+            }
+        }
+    }
+}
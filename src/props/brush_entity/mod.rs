@@ -1,6 +1,7 @@
 use bevy::prelude::*;
+mod hazard;
 mod light_window;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(light_window::plugin);
+    app.add_plugins((hazard::plugin, light_window::plugin));
 }
@@ -1,6 +1,8 @@
 use crate::gameplay::{
-    explosion::{ExplodeOnShoot, effects::PropExplosionVfx},
+    explosion::{ExplodeOnShoot, Explosive, effects::PropExplosionVfx},
     health::Health,
+    status_effects::{StatusEffectKind, StatusEffectSpec},
+    surface::SurfaceMaterial,
 };
 
 use super::setup::*;
@@ -40,6 +42,7 @@ pub(super) fn plugin(app: &mut App) {
 #[base(Transform, Visibility)]
 #[model("models/darkmod/containers/package_medium.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct PackageMedium;
 
 #[derive(PointClass, Component, Debug, Reflect)]
@@ -47,6 +50,7 @@ pub(crate) struct PackageMedium;
 #[base(Transform, Visibility)]
 #[model("models/darkmod/containers/package_small.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct PackageSmall;
 
 // generic static props
@@ -56,6 +60,7 @@ pub(crate) struct PackageSmall;
 #[base(Transform, Visibility)]
 #[model("models/darkmod/furniture/tables/rtable1.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct Table;
 
 #[derive(PointClass, Component, Debug, Reflect)]
@@ -63,6 +68,7 @@ pub(crate) struct Table;
 #[base(Transform, Visibility)]
 #[model("models/darkmod/furniture/shelves/bookshelf02.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct Bookshelf;
 
 #[derive(PointClass, Component, Debug, Reflect)]
@@ -70,6 +76,7 @@ pub(crate) struct Bookshelf;
 #[base(Transform, Visibility)]
 #[model("models/darkmod/mechanical/generator2/generator2.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Metal)]
 pub(crate) struct Generator2;
 
 #[derive(PointClass, Component, Debug, Reflect)]
@@ -77,14 +84,25 @@ pub(crate) struct Generator2;
 #[base(Transform, Visibility)]
 #[model("models/darkmod/containers/barrel_large_closed.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
-#[require(ExplodeOnShoot, PropExplosionVfx, Health = Health::new(10.0))]
+#[require(ExplodeOnShoot, PropExplosionVfx, Explosive = BarrelLargeClosed::explosive(), Health = Health::new(10.0), SurfaceMaterial = SurfaceMaterial::Metal)]
 pub(crate) struct BarrelLargeClosed;
 
+impl BarrelLargeClosed {
+    /// The barrel is full of fuel, so it sets everything caught in the blast on fire.
+    fn explosive() -> Explosive {
+        Explosive {
+            status_effect: Some(StatusEffectSpec::new(StatusEffectKind::Burning, 3.0)),
+            ..default()
+        }
+    }
+}
+
 #[derive(PointClass, Component, Debug, Reflect)]
 #[reflect(QuakeClass, Component)]
 #[base(Transform, Visibility)]
 #[model("models/darkmod/containers/barrel01.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct Barrel01;
 
 #[derive(PointClass, Component, Debug, Reflect)]
@@ -92,6 +110,7 @@ pub(crate) struct Barrel01;
 #[base(Transform, Visibility)]
 #[model("models/darkmod/containers/crate_square.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct CrateSquare;
 
 #[derive(PointClass, Component, Debug, Reflect)]
@@ -99,6 +118,7 @@ pub(crate) struct CrateSquare;
 #[base(Transform, Visibility)]
 #[model("models/darkmod/architecture/fencing/fence_bars_decorative01_single.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Metal)]
 pub(crate) struct FenceBarsDecorativeSingle;
 
 // Generic non-physical props
@@ -6,6 +6,7 @@ use bevy::prelude::*;
 mod brush_entity;
 mod effects;
 pub(crate) mod generic;
+pub(crate) mod nav_obstacle;
 mod setup;
 mod specific;
 
@@ -16,5 +17,6 @@ pub(super) fn plugin(app: &mut App) {
         effects::plugin,
         generic::plugin,
         brush_entity::plugin,
+        nav_obstacle::plugin,
     ));
 }
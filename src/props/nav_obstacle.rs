@@ -0,0 +1,118 @@
+//! Dynamic props as navmesh obstacles. The navmesh is built from static geometry,
+//! so large physics props that come to rest are carved into it until they get moved again.
+//!
+//! Every carve or uncarve makes Oxidized Navigation rebuild the tiles under the prop.
+//! To keep chain explosions from causing a rebuild storm, props only carve themselves
+//! after resting for a while, and only a few props may do so per second.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use oxidized_navigation::NavMeshAffector;
+
+use crate::{PostPhysicsAppSystems, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DynamicNavObstacle>();
+    app.init_resource::<CarveBudget>();
+    app.add_systems(
+        Update,
+        update_dynamic_nav_obstacles
+            .in_set(PostPhysicsAppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How long a prop has to rest before it is carved into the navmesh.
+const SETTLE_SECS: f32 = 1.5;
+/// Props below this speed count as resting, even if the physics engine hasn't put them to sleep yet.
+const REST_SPEED: f32 = 0.05;
+/// Props with a smaller footprint are left to local avoidance.
+const MIN_FOOTPRINT: f32 = 0.8;
+/// How many props may be carved into the navmesh per second, and how many at once.
+const CARVES_PER_SEC: f32 = 2.0;
+const MAX_CARVE_BURST: f32 = 4.0;
+
+/// A physics prop that is carved into the navmesh while it rests, if it is large enough.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct DynamicNavObstacle {
+    rest_timer: Timer,
+    carved: bool,
+}
+
+impl Default for DynamicNavObstacle {
+    fn default() -> Self {
+        Self {
+            rest_timer: Timer::from_seconds(SETTLE_SECS, TimerMode::Once),
+            carved: false,
+        }
+    }
+}
+
+/// Limits how many props are carved into the navmesh per second.
+#[derive(Resource, Debug)]
+struct CarveBudget(f32);
+
+impl Default for CarveBudget {
+    fn default() -> Self {
+        Self(MAX_CARVE_BURST)
+    }
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn update_dynamic_nav_obstacles(
+    mut props: Query<(
+        NameOrEntity,
+        &mut DynamicNavObstacle,
+        &LinearVelocity,
+        &AngularVelocity,
+        &RigidBodyColliders,
+        Has<Sleeping>,
+    )>,
+    aabbs: Query<&ColliderAabb>,
+    mut budget: ResMut<CarveBudget>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    budget.0 = (budget.0 + CARVES_PER_SEC * time.delta_secs()).min(MAX_CARVE_BURST);
+    for (name, mut obstacle, linear_velocity, angular_velocity, colliders, sleeping) in &mut props {
+        let resting = sleeping
+            || (linear_velocity.length() < REST_SPEED && angular_velocity.length() < REST_SPEED);
+        if !resting {
+            obstacle.rest_timer.reset();
+            // Uncarving right away stops the moving prop from dirtying the navmesh every frame.
+            if obstacle.carved {
+                obstacle.carved = false;
+                for collider in colliders.iter() {
+                    commands.entity(collider).try_remove::<NavMeshAffector>();
+                }
+                debug!("{name} started moving, removed it from the navmesh");
+            }
+            continue;
+        }
+        if obstacle.carved {
+            continue;
+        }
+        obstacle.rest_timer.tick(time.delta());
+        if !obstacle.rest_timer.finished() || budget.0 < 1.0 {
+            continue;
+        }
+
+        let size = aabbs
+            .iter_many(colliders.iter())
+            .map(|aabb| (aabb.min, aabb.max))
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+            .map_or(Vec3::ZERO, |(min, max)| max - min);
+        if size.x.max(size.z) < MIN_FOOTPRINT {
+            continue;
+        }
+        obstacle.carved = true;
+        budget.0 -= 1.0;
+        for collider in colliders.iter() {
+            commands.entity(collider).try_insert(NavMeshAffector);
+        }
+        debug!("{name} came to rest, carved it into the navmesh");
+    }
+}
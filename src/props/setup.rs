@@ -2,6 +2,7 @@
 //! A *dynamic* prop in the context of this file is a prop that is influenced by physics,
 //! while a *static* prop is unmovable terrain.
 
+use crate::props::nav_obstacle::DynamicNavObstacle;
 use crate::third_party::avian3d::CollisionLayer;
 use crate::third_party::bevy_landmass::NavMeshAffectorParent;
 use crate::third_party::bevy_trenchbroom::LoadTrenchbroomModel as _;
@@ -71,6 +72,7 @@ pub(crate) fn dynamic_bundle<T: QuakeClass>(
         // `TnuaNotPlatform` ensures that the character controller will not try to walk on the prop.
         // Removing this will make it so that throwing a prop at a controller sends them flying so that they stand on top of it.
         TnuaNotPlatform,
+        DynamicNavObstacle::default(),
         SceneRoot(model),
     )
 }
@@ -5,7 +5,14 @@ use bevy_landmass::{Character, prelude::*};
 use bevy_simple_subsecond_system::hot;
 use bevy_trenchbroom::prelude::*;
 
-use crate::third_party::{avian3d::CollisionLayer, bevy_trenchbroom::LoadTrenchbroomModel as _};
+use crate::{
+    gameplay::surface::SurfaceMaterial,
+    props::nav_obstacle::DynamicNavObstacle,
+    third_party::{
+        avian3d::CollisionLayer, bevy_landmass::BaseArchipelago,
+        bevy_trenchbroom::LoadTrenchbroomModel as _,
+    },
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(setup_chair);
@@ -17,6 +24,7 @@ pub(super) fn plugin(app: &mut App) {
 #[base(Transform, Visibility)]
 #[model("models/darkmod/furniture/seating/wchair1.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct Chair;
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -24,7 +32,7 @@ fn setup_chair(
     trigger: Trigger<OnAdd, Chair>,
     asset_server: Res<AssetServer>,
     mut commands: Commands,
-    archipelago: Single<Entity, With<Archipelago3d>>,
+    archipelago: Single<Entity, With<BaseArchipelago>>,
 ) {
     let model = asset_server.load_trenchbroom_model::<Chair>();
     commands.entity(trigger.target()).insert(Character3dBundle {
@@ -40,6 +48,7 @@ fn setup_chair(
             // Make the chair way more dense than the default, as it feels janky to be able to push it around easily.
             .with_default_density(10_000.0),
         RigidBody::Dynamic,
+        DynamicNavObstacle::default(),
         // Not inserting `TnuaNotPlatform`, otherwise the player will not be able to jump on it.
         SceneRoot(model),
     ));
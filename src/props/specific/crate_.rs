@@ -6,8 +6,12 @@ use bevy_simple_subsecond_system::hot;
 use bevy_trenchbroom::prelude::*;
 
 use crate::{
-    props::setup::setup_dynamic_prop_with_convex_hull,
-    third_party::{avian3d::CollisionLayer, bevy_trenchbroom::LoadTrenchbroomModel as _},
+    gameplay::surface::SurfaceMaterial,
+    props::{nav_obstacle::DynamicNavObstacle, setup::setup_dynamic_prop_with_convex_hull},
+    third_party::{
+        avian3d::CollisionLayer, bevy_landmass::BaseArchipelago,
+        bevy_trenchbroom::LoadTrenchbroomModel as _,
+    },
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -22,6 +26,7 @@ pub(super) fn plugin(app: &mut App) {
 #[base(Transform, Visibility)]
 #[model("models/darkmod/containers/crate01_big.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct CrateBig;
 
 #[derive(PointClass, Component, Debug, Reflect)]
@@ -29,6 +34,7 @@ pub(crate) struct CrateBig;
 #[base(Transform, Visibility)]
 #[model("models/darkmod/containers/crate01_small.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Wood)]
 pub(crate) struct CrateSmall;
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -36,7 +42,7 @@ fn setup_crate_small(
     trigger: Trigger<OnAdd, CrateSmall>,
     asset_server: Res<AssetServer>,
     mut commands: Commands,
-    archipelago: Single<Entity, With<Archipelago3d>>,
+    archipelago: Single<Entity, With<BaseArchipelago>>,
 ) {
     let model = asset_server.load_trenchbroom_model::<CrateSmall>();
     commands.entity(trigger.target()).insert(Character3dBundle {
@@ -51,5 +57,6 @@ fn setup_crate_small(
         // Not inserting `TnuaNotPlatform`, otherwise the player will not be able to jump on it.
         SceneRoot(model),
         RigidBody::Dynamic,
+        DynamicNavObstacle::default(),
     ));
 }
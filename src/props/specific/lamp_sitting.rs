@@ -4,7 +4,10 @@ use bevy::prelude::*;
 use bevy_simple_subsecond_system::hot;
 use bevy_trenchbroom::prelude::*;
 
-use crate::props::{effects::disable_shadow_casting_on_instance_ready, setup::dynamic_bundle};
+use crate::{
+    gameplay::surface::SurfaceMaterial,
+    props::{effects::disable_shadow_casting_on_instance_ready, setup::dynamic_bundle},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(setup_lamp_sitting);
@@ -18,6 +21,7 @@ pub(super) fn plugin(app: &mut App) {
     "models/darkmod/lights/non-extinguishable/round_lantern_sitting/round_lantern_sitting.gltf"
 )]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
+#[require(SurfaceMaterial = SurfaceMaterial::Metal)]
 pub(crate) struct LampSitting;
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -25,6 +25,8 @@ pub(super) fn plugin(app: &mut App) {
             close_menu.run_if(
                 in_state(Screen::Gameplay)
                     .and(not(in_state(Menu::None)))
+                    // P can't be bound, but pressing it while rebinding should explain why instead of closing the menu.
+                    .and(not(in_state(Menu::Controls)))
                     .and(input_just_pressed(KeyCode::KeyP)),
             ),
         ),
@@ -1,6 +1,6 @@
 use crate::asset_tracking::LoadResource as _;
 use crate::gameplay::explosion::ExplodeOnShoot;
-use crate::gameplay::health::OnDamage;
+use crate::gameplay::health::{DamageKind, OnDamage};
 use crate::gameplay::npc::ai_state::AiState;
 use crate::gameplay::player::Player;
 use crate::gameplay::player::camera::PlayerCamera;
@@ -178,7 +178,9 @@ fn explode_enemy(
     }
     *already_exploded = true;
     for entity in &enemies {
-        commands.entity(entity).trigger(OnDamage(1000.0));
+        commands
+            .entity(entity)
+            .trigger(OnDamage::new(1000.0, DamageKind::Generic));
     }
 }
 
@@ -200,7 +202,9 @@ fn explode_barrel(
     }
     *already_exploded = true;
     for entity in &barrels {
-        commands.entity(entity).trigger(OnDamage(1000.0));
+        commands
+            .entity(entity)
+            .trigger(OnDamage::new(1000.0, DamageKind::Generic));
     }
 }
 
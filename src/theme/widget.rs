@@ -72,6 +72,7 @@ where
     button_base(
         text,
         font,
+        32.0,
         action,
         (
             Node {
@@ -101,6 +102,7 @@ where
     button_base(
         text,
         font,
+        32.0,
         action,
         (
             Node {
@@ -116,10 +118,41 @@ where
     )
 }
 
+/// A wide, flat button with smaller text and an action defined as an [`Observer`]. Fits in dense lists.
+pub(crate) fn button_medium<E, B, M, I>(
+    text: impl Into<String>,
+    font: Handle<Font>,
+    action: I,
+) -> impl Bundle
+where
+    E: Event,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    button_base(
+        text,
+        font,
+        20.0,
+        action,
+        (
+            Node {
+                width: Px(220.0),
+                height: Px(32.0),
+                border: UiRect::all(Px(2.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BorderRadius::all(Px(5.0)),
+        ),
+    )
+}
+
 /// A simple button with text and an action defined as an [`Observer`]. The button's layout is provided by `button_bundle`.
 fn button_base<E, B, M, I>(
     text: impl Into<String>,
     font: Handle<Font>,
+    font_size: f32,
     action: I,
     button_bundle: impl Bundle,
 ) -> impl Bundle
@@ -133,7 +166,7 @@ where
     (
         Name::new("Button"),
         Node::default(),
-        Children::spawn(SpawnWith(|parent: &mut ChildSpawner| {
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
             parent
                 .spawn((
                     Name::new("Button Inner"),
@@ -148,7 +181,7 @@ where
                         Name::new("Button Text"),
                         Text(text),
                         TextLayout::new_with_justify(JustifyText::Center),
-                        TextFont::from_font_size(32.0).with_font(font),
+                        TextFont::from_font_size(font_size).with_font(font),
                         TextColor(BUTTON_TEXT),
                         // Don't bubble picking events from the text up to the button.
                         Pickable::IGNORE,
@@ -20,6 +20,7 @@ pub(crate) enum CollisionLayer {
     Sensor,
     Npc,
     Gib,
+    Projectile,
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -1,17 +1,28 @@
 //! [Landmass](https://github.com/andriyDev/landmass) powers out agent navigation.
 //! The underlying navmesh is generated using [Oxidized Navigation](https://github.com/TheGrimsey/oxidized_navigation).
+//!
+//! NPCs are sorted into [`NavTier`]s by their radius, and every tier has its own archipelago.
+//! Oxidized Navigation can only generate a single navmesh, which is eroded for the smallest tier.
+//! The larger tiers build their islands from its tiles, eroded further by the difference in radius,
+//! so that big NPCs don't path through gaps they don't fit.
 
 use super::bevy_trenchbroom::Worldspawn;
 use crate::gameplay::npc::{NPC_HEIGHT, NPC_RADIUS, navigation::NPC_MAX_SLOPE};
 use avian3d::prelude::*;
 use bevy::ecs::relationship::Relationship as _;
-use bevy::prelude::*;
-use bevy_landmass::{PointSampleDistance3d, prelude::*};
+use bevy::{platform::collections::HashMap, prelude::*};
+use bevy_landmass::{NavMesh3d, NavigationMesh3d, PointSampleDistance3d, prelude::*};
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
 use landmass_oxidized_navigation::{LandmassOxidizedNavigationPlugin, OxidizedArchipelago};
 use oxidized_navigation::{
-    NavMeshAffector, NavMeshSettings, OxidizedNavigationPlugin, colliders::avian::AvianCollider,
+    NavMesh as OxidizedNavMesh, NavMeshAffector, NavMeshSettings, OxidizedNavigationPlugin,
+    colliders::avian::AvianCollider,
+    tiles::{EdgeConnection, NavMeshTile},
+};
+use std::{
+    hash::{DefaultHasher, Hash as _, Hasher as _},
+    sync::Arc,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -30,29 +41,232 @@ pub(super) fn plugin(app: &mut App) {
             )
         }),
     ));
-    app.add_systems(Startup, setup_archipelago);
+    app.register_type::<(NavTier, ErodedIsland)>();
+    app.add_systems(Startup, setup_archipelagos);
+    app.add_systems(Update, erode_islands);
     app.add_observer(add_nav_mesh_affector_to_trenchbroom_worldspawn);
     app.add_observer(add_nav_mesh_affector_to_colliders_under_nav_mesh_affector_parent);
 }
 
+/// The agent radii of the navigation tiers, from smallest to largest.
+/// An NPC uses the smallest tier that fits its radius.
+pub(crate) const NAV_TIER_RADII: [f32; 2] = [NPC_RADIUS, NPC_RADIUS * 2.0];
+
+/// An archipelago for NPCs up to a certain size.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub(crate) struct NavTier {
+    pub(crate) agent_radius: f32,
+}
+
+/// Marks the archipelago of the smallest tier, which is the one the navmesh is generated for.
+/// Use it for anything that is not tied to a specific NPC, like sampling the player's position.
+#[derive(Component, Debug)]
+pub(crate) struct BaseArchipelago;
+
+/// An island of a larger tier's archipelago, built from one tile of the navmesh that
+/// Oxidized Navigation generates for the [`BaseArchipelago`].
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct ErodedIsland {
+    tile: UVec2,
+    tile_hash: u64,
+}
+
 #[cfg_attr(feature = "hot_patch", hot)]
-fn setup_archipelago(mut commands: Commands) {
+fn setup_archipelagos(mut commands: Commands) {
     // This *should* be scoped to the `Screen::Gameplay` state, but doing so
     // seems to never regenerate the nav mesh when the level is loaded the second
     // time.
-    commands.spawn((
-        Name::new("Main Level Archipelago"),
-        Archipelago3d::new(AgentOptions {
-            point_sample_distance: PointSampleDistance3d {
-                horizontal_distance: 0.6,
-                distance_above: 1.0,
-                distance_below: 1.0,
-                vertical_preference_ratio: 2.0,
-            },
-            ..AgentOptions::from_agent_radius(NPC_RADIUS)
-        }),
-        OxidizedArchipelago,
-    ));
+    for (i, agent_radius) in NAV_TIER_RADII.into_iter().enumerate() {
+        let mut archipelago = commands.spawn((
+            Name::new(format!("Main Level Archipelago (Tier {i})")),
+            NavTier { agent_radius },
+            Archipelago3d::new(AgentOptions {
+                point_sample_distance: PointSampleDistance3d {
+                    horizontal_distance: 0.6 * agent_radius / NPC_RADIUS,
+                    distance_above: 1.0,
+                    distance_below: 1.0,
+                    vertical_preference_ratio: 2.0,
+                },
+                ..AgentOptions::from_agent_radius(agent_radius)
+            }),
+        ));
+        if i == 0 {
+            archipelago.insert((BaseArchipelago, OxidizedArchipelago));
+        }
+    }
+}
+
+/// Rebuilds the islands of the larger tiers whenever Oxidized Navigation regenerates a tile.
+/// Every tile is eroded by how much larger the tier's agents are than the base tier's.
+#[cfg_attr(feature = "hot_patch", hot)]
+fn erode_islands(
+    changed_islands: Query<
+        (),
+        (
+            With<Island>,
+            Without<ErodedIsland>,
+            Changed<NavMeshHandle3d>,
+        ),
+    >,
+    mut removed_islands: RemovedComponents<Island>,
+    oxidized_nav_mesh: Res<OxidizedNavMesh>,
+    tiers: Query<(Entity, &NavTier), Without<BaseArchipelago>>,
+    eroded_islands: Query<(Entity, &ErodedIsland, &ArchipelagoRef3d)>,
+    mut nav_meshes: ResMut<Assets<NavMesh3d>>,
+    mut commands: Commands,
+) {
+    let removed_any = removed_islands.read().count() > 0;
+    if changed_islands.is_empty() && !removed_any {
+        return;
+    }
+    let Ok(tiles) = oxidized_nav_mesh.get().read() else {
+        return;
+    };
+
+    let mut stale = HashMap::new();
+    for (island, eroded, archipelago) in &eroded_islands {
+        stale.insert(
+            (archipelago.entity, eroded.tile),
+            (island, eroded.tile_hash),
+        );
+    }
+    for (tier, nav_tier) in &tiers {
+        let extra_radius = nav_tier.agent_radius - NAV_TIER_RADII[0];
+        for (&tile, tile_mesh) in &tiles.tiles {
+            let tile_hash = hash_tile(tile_mesh);
+            let previous = stale.remove(&(tier, tile));
+            if let Some((island, previous_hash)) = previous {
+                if previous_hash == tile_hash {
+                    continue;
+                }
+                commands.entity(island).despawn();
+            }
+            let Some(nav_mesh) = erode_tile(tile_mesh, extra_radius) else {
+                continue;
+            };
+            commands.spawn((
+                Name::new(format!("Eroded Island {tile}")),
+                Island3dBundle {
+                    island: Island,
+                    archipelago_ref: ArchipelagoRef3d::new(tier),
+                    nav_mesh: NavMeshHandle3d(nav_meshes.add(nav_mesh)),
+                },
+                Transform::default(),
+                ErodedIsland { tile, tile_hash },
+            ));
+        }
+    }
+    for (island, _) in stale.into_values() {
+        commands.entity(island).despawn();
+    }
+}
+
+fn hash_tile(tile: &NavMeshTile) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for vertex in &tile.vertices {
+        vertex.to_array().map(f32::to_bits).hash(&mut hasher);
+    }
+    for polygon in &tile.polygons {
+        polygon.indices.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Triangles that shrink below this area during erosion are dropped.
+const MIN_ERODED_AREA: f32 = 0.001;
+
+/// Pushes the walls of a tile inward by `extra_radius`. Triangles that fold over in the process
+/// are the ones in gaps too narrow for the tier's agents, so they are dropped.
+fn erode_tile(tile: &NavMeshTile, extra_radius: f32) -> Option<NavMesh3d> {
+    let mut wall_normals = vec![Vec::<Vec2>::new(); tile.vertices.len()];
+    for (polygon, edges) in tile.polygons.iter().zip(&tile.edges) {
+        let corners = polygon
+            .indices
+            .map(|index| tile.vertices[index as usize].xz());
+        let centroid = corners.iter().sum::<Vec2>() / corners.len() as f32;
+        for (i, edge) in edges.iter().enumerate() {
+            // Edges without a connection border a wall or a ledge.
+            if !matches!(edge, EdgeConnection::None) {
+                continue;
+            }
+            let next = (i + 1) % corners.len();
+            let mut normal = (corners[next] - corners[i]).perp().normalize_or_zero();
+            if normal.dot(centroid - corners[i]) < 0.0 {
+                normal = -normal;
+            }
+            wall_normals[polygon.indices[i] as usize].push(normal);
+            wall_normals[polygon.indices[next] as usize].push(normal);
+        }
+    }
+    let vertices: Vec<Vec3> = tile
+        .vertices
+        .iter()
+        .zip(&wall_normals)
+        .map(|(vertex, normals)| {
+            let offset = wall_offset(normals, extra_radius);
+            vertex + Vec3::new(offset.x, 0.0, offset.y)
+        })
+        .collect();
+
+    let mut remapped = vec![None; vertices.len()];
+    let mut eroded_vertices = Vec::new();
+    let mut polygons = Vec::new();
+    for polygon in &tile.polygons {
+        let original = signed_area(
+            polygon
+                .indices
+                .map(|index| tile.vertices[index as usize].xz()),
+        );
+        let eroded = signed_area(polygon.indices.map(|index| vertices[index as usize].xz()));
+        if original.signum() != eroded.signum() || eroded.abs() < MIN_ERODED_AREA {
+            continue;
+        }
+        let indices = polygon.indices.map(|index| {
+            let index = index as usize;
+            *remapped[index].get_or_insert_with(|| {
+                eroded_vertices.push(vertices[index]);
+                eroded_vertices.len() - 1
+            })
+        });
+        polygons.push(indices.to_vec());
+    }
+    if polygons.is_empty() {
+        return None;
+    }
+
+    let polygon_type_indices = vec![0; polygons.len()];
+    let nav_mesh = NavigationMesh3d {
+        vertices: eroded_vertices,
+        polygons,
+        polygon_type_indices,
+        height_mesh: None,
+    }
+    .validate()
+    .inspect_err(|error| warn!("Failed to erode navmesh tile: {error:?}"))
+    .ok()?;
+    Some(NavMesh3d {
+        nav_mesh: Arc::new(nav_mesh),
+        type_index_to_node_type: default(),
+    })
+}
+
+/// How far to move a vertex so that every wall it touches ends up `radius` further away.
+/// The miter is capped so that sharp corners don't shoot across the tile.
+fn wall_offset(normals: &[Vec2], radius: f32) -> Vec2 {
+    let Some(direction) = normals.iter().sum::<Vec2>().try_normalize() else {
+        return Vec2::ZERO;
+    };
+    let closest = normals
+        .iter()
+        .map(|normal| normal.dot(direction))
+        .fold(1.0, f32::min);
+    direction * radius / closest.max(0.5)
+}
+
+fn signed_area([a, b, c]: [Vec2; 3]) -> f32 {
+    (b - a).perp_dot(c - a) * 0.5
 }
 
 #[derive(Component)]
@@ -5,10 +5,21 @@ use bevy_trenchbroom::{class::QuakeClass, prelude::*};
 
 pub(super) fn plugin(_app: &mut App) {}
 
-#[derive(SolidClass, Component, Reflect, Default)]
+#[derive(SolidClass, Component, Reflect)]
 #[reflect(Component, QuakeClass)]
 #[spawn_hooks(SpawnHooks::new().convex_collider().smooth_by_default_angle())]
-pub(crate) struct Worldspawn;
+pub(crate) struct Worldspawn {
+    /// Everything that falls below this height dies.
+    pub(crate) kill_plane_height: f32,
+}
+
+impl Default for Worldspawn {
+    fn default() -> Self {
+        Self {
+            kill_plane_height: -300.0,
+        }
+    }
+}
 
 pub(crate) trait GetTrenchbroomModelPath: QuakeClass {
     fn model_path() -> String {